@@ -705,11 +705,11 @@ impl DemoApp {
 
                     ui.horizontal(|ui| {
                         ui.add(egui::Slider::new(&mut state.row_dist, 10.0..=500.0).text("row_dist"));
-                        info_icon(ui, "Distance between levels (rows). For LeftRight, this is X step.");
+                        info_icon(ui, "Distance between levels (rows). For LeftRight/RightLeft, this is X step.");
                     });
                     ui.horizontal(|ui| {
                         ui.add(egui::Slider::new(&mut state.col_dist, 10.0..=800.0).text("col_dist"));
-                        info_icon(ui, "Distance between siblings (columns). For LeftRight, this is Y step.");
+                        info_icon(ui, "Distance between siblings (columns). For LeftRight/RightLeft, this is Y step.");
                     });
                     ui.horizontal(|ui| {
                         ui.checkbox(&mut state.center_parent, "center_parent");
@@ -718,6 +718,8 @@ impl DemoApp {
                     ui.horizontal(|ui| {
                         ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::TopDown, "TopDown");
                         ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::LeftRight, "LeftRight");
+                        ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::BottomUp, "BottomUp");
+                        ui.selectable_value(&mut state.orientation, LayoutHierarchicalOrientation::RightLeft, "RightLeft");
                     });
                     ui.horizontal(|ui| {
                         if ui.button("Re-run layout").clicked() {
@@ -749,14 +751,14 @@ impl DemoApp {
                     ui.add(egui::Slider::new(&mut state.row_dist, 10.0..=500.0).text("row_dist"));
                     info_icon(
                         ui,
-                        "Distance between levels (rows). For LeftRight, this is X step.",
+                        "Distance between levels (rows). For LeftRight/RightLeft, this is X step.",
                     );
                 });
                 ui.horizontal(|ui| {
                     ui.add(egui::Slider::new(&mut state.col_dist, 10.0..=800.0).text("col_dist"));
                     info_icon(
                         ui,
-                        "Distance between siblings (columns). For LeftRight, this is Y step.",
+                        "Distance between siblings (columns). For LeftRight/RightLeft, this is Y step.",
                     );
                 });
                 ui.horizontal(|ui| {
@@ -782,6 +784,26 @@ impl DemoApp {
                     {
                         state.orientation = o;
                     }
+                    if ui
+                        .selectable_value(
+                            &mut o,
+                            LayoutHierarchicalOrientation::BottomUp,
+                            "BottomUp",
+                        )
+                        .clicked()
+                    {
+                        state.orientation = o;
+                    }
+                    if ui
+                        .selectable_value(
+                            &mut o,
+                            LayoutHierarchicalOrientation::RightLeft,
+                            "RightLeft",
+                        )
+                        .clicked()
+                    {
+                        state.orientation = o;
+                    }
                 });
 
                 ui.add_space(SECTION_SPACING);
@@ -807,6 +829,15 @@ impl DemoApp {
                 }
                 info_icon(ui, "Master: also enables node_clicking and hover.");
             });
+            ui.add_enabled_ui(self.settings_interaction.dragging_enabled, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(
+                        &mut self.settings_interaction.node_pin_on_drag_enabled,
+                        "node_pin_on_drag_enabled",
+                    );
+                    info_icon(ui, "Pin dragged nodes so layouts leave them where they were dropped.");
+                });
+            });
             ui.add_enabled_ui(
                 !self.settings_interaction.dragging_enabled
                     && !self.settings_interaction.node_selection_enabled
@@ -904,6 +935,36 @@ impl DemoApp {
                 }
                 info_icon(ui, "Master: also enables selection, node_clicking and hover.");
             });
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut self.settings_interaction.box_selection_enabled, "box_selection")
+                    .changed()
+                    && self.settings_interaction.box_selection_enabled
+                {
+                    self.settings_interaction.node_selection_enabled = true;
+                    self.settings_interaction.node_clicking_enabled = true;
+                    self.settings_interaction.hover_enabled = true;
+                }
+                info_icon(
+                    ui,
+                    "Shift + drag selects a rectangle, add Ctrl/Cmd to add, Alt to remove. Master: also enables node_selection, node_clicking and hover.",
+                );
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut self.settings_interaction.lasso_selection_enabled, "lasso_selection")
+                    .changed()
+                    && self.settings_interaction.lasso_selection_enabled
+                {
+                    self.settings_interaction.node_selection_enabled = true;
+                    self.settings_interaction.node_clicking_enabled = true;
+                    self.settings_interaction.hover_enabled = true;
+                }
+                info_icon(
+                    ui,
+                    "Shift + drag draws a freeform selection instead of a rectangle, same modifiers. Master: also enables node_selection, node_clicking and hover.",
+                );
+            });
         });
     }
 
@@ -1313,13 +1374,16 @@ impl App for DemoApp {
                     self.settings_interaction.node_selection_multi_enabled,
                 )
                 .with_dragging_enabled(self.settings_interaction.dragging_enabled)
+                .with_node_pin_on_drag_enabled(self.settings_interaction.node_pin_on_drag_enabled)
                 .with_hover_enabled(self.settings_interaction.hover_enabled)
                 .with_node_clicking_enabled(self.settings_interaction.node_clicking_enabled)
                 .with_edge_clicking_enabled(self.settings_interaction.edge_clicking_enabled)
                 .with_edge_selection_enabled(self.settings_interaction.edge_selection_enabled)
                 .with_edge_selection_multi_enabled(
                     self.settings_interaction.edge_selection_multi_enabled,
-                );
+                )
+                .with_box_selection_enabled(self.settings_interaction.box_selection_enabled)
+                .with_lasso_selection_enabled(self.settings_interaction.lasso_selection_enabled);
             let settings_navigation = &egui_graphs::SettingsNavigation::new()
                 .with_zoom_and_pan_enabled(self.settings_navigation.zoom_and_pan_enabled)
                 .with_fit_to_screen_enabled(self.settings_navigation.fit_to_screen_enabled)
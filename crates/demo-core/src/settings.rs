@@ -3,6 +3,7 @@
 // Interaction-related toggles
 pub struct SettingsInteraction {
     pub dragging_enabled: bool,
+    pub node_pin_on_drag_enabled: bool,
     pub hover_enabled: bool,
     pub node_clicking_enabled: bool,
     pub node_selection_enabled: bool,
@@ -10,12 +11,15 @@ pub struct SettingsInteraction {
     pub edge_clicking_enabled: bool,
     pub edge_selection_enabled: bool,
     pub edge_selection_multi_enabled: bool,
+    pub box_selection_enabled: bool,
+    pub lasso_selection_enabled: bool,
 }
 
 impl Default for SettingsInteraction {
     fn default() -> Self {
         Self {
             dragging_enabled: true,
+            node_pin_on_drag_enabled: false,
             hover_enabled: true,
             node_clicking_enabled: false,
             node_selection_enabled: false,
@@ -23,6 +27,8 @@ impl Default for SettingsInteraction {
             edge_clicking_enabled: false,
             edge_selection_enabled: false,
             edge_selection_multi_enabled: false,
+            box_selection_enabled: false,
+            lasso_selection_enabled: false,
         }
     }
 }
@@ -67,6 +67,8 @@ pub enum ExtrasSpec {
 pub enum HierOrientationSpec {
     TopDown,
     LeftRight,
+    BottomUp,
+    RightLeft,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -174,6 +176,12 @@ impl LayoutSpec {
                         HierOrientationSpec::LeftRight => {
                             egui_graphs::LayoutHierarchicalOrientation::LeftRight
                         }
+                        HierOrientationSpec::BottomUp => {
+                            egui_graphs::LayoutHierarchicalOrientation::BottomUp
+                        }
+                        HierOrientationSpec::RightLeft => {
+                            egui_graphs::LayoutHierarchicalOrientation::RightLeft
+                        }
                     };
                 }
                 // Trigger re-run once
@@ -217,6 +225,12 @@ impl PendingLayout {
                 egui_graphs::LayoutHierarchicalOrientation::LeftRight => {
                     HierOrientationSpec::LeftRight
                 }
+                egui_graphs::LayoutHierarchicalOrientation::BottomUp => {
+                    HierOrientationSpec::BottomUp
+                }
+                egui_graphs::LayoutHierarchicalOrientation::RightLeft => {
+                    HierOrientationSpec::RightLeft
+                }
             }),
         }
     }
@@ -0,0 +1,129 @@
+use criterion::measurement::WallTime;
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion};
+use egui::{Pos2, RawInput, Rect, Vec2};
+use egui_graphs::{
+    to_graph, DefaultEdgeShape, DefaultNodeShape, Graph, GraphView, SettingsNavigation,
+    SettingsStyle,
+};
+use petgraph::stable_graph::{DefaultIx, NodeIndex, StableGraph};
+use std::hint::black_box;
+use std::time::Duration;
+
+type G<N> = Graph<N, (), petgraph::Directed, DefaultIx, DefaultNodeShape, DefaultEdgeShape>;
+
+/// Frames run before timing, so first frame work (fit to screen, initial layout and
+/// spatial index build) is not measured.
+const WARM_UP_FRAMES: usize = 3;
+
+fn make_graph<N: Clone>(num_nodes: usize, num_edges: usize, payload: &N) -> G<N> {
+    let mut g: StableGraph<N, ()> = StableGraph::default();
+    for _ in 0..num_nodes {
+        g.add_node(payload.clone());
+    }
+    // chain plus deterministic extra edges, as in the FR bench
+    for i in 1..num_nodes {
+        g.add_edge(NodeIndex::new(i - 1), NodeIndex::new(i), ());
+    }
+    let mut extra = num_edges.saturating_sub(num_nodes.saturating_sub(1));
+    let mut i = 0usize;
+    while extra > 0 && num_nodes >= 2 {
+        let a = i % num_nodes;
+        let b = (i * 37 + 11) % num_nodes;
+        if a != b {
+            g.add_edge(NodeIndex::new(a), NodeIndex::new(b), ());
+            extra -= 1;
+        }
+        i += 1;
+    }
+
+    let mut graph = to_graph(&g);
+    let side = (num_nodes as f32).sqrt().ceil() as usize;
+    let idxs: Vec<_> = graph.g().node_indices().collect();
+    for (i, idx) in idxs.into_iter().enumerate() {
+        graph.node_mut(idx).unwrap().set_location(Pos2::new(
+            (i % side) as f32 * 20.0,
+            (i / side) as f32 * 20.0,
+        ));
+    }
+    graph
+}
+
+/// Runs one headless egui frame showing the graph.
+///
+/// With `fit` the whole graph is in view; without it the view stays at zoom 1, where
+/// only a corner of the graph covers the screen.
+fn frame<N: Clone>(ctx: &egui::Context, g: &mut G<N>, culling: bool, fit: bool) {
+    let input = RawInput {
+        screen_rect: Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(1200.0, 800.0))),
+        ..RawInput::default()
+    };
+    let style = SettingsStyle::new().with_viewport_culling(culling);
+    let navigation = SettingsNavigation::new().with_fit_to_screen_enabled(fit);
+    let out = ctx.run_ui(input, |ui| {
+        ui.add(
+            &mut GraphView::<_, _, _, _, _, _>::new(g)
+                .with_styles(&style)
+                .with_navigations(&navigation),
+        );
+    });
+    black_box(out);
+}
+
+/// Times frames drawn by one long lived context over one graph, as an app would draw them.
+fn bench_frames<N: Clone>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    payload: &N,
+    culling: bool,
+    fit: bool,
+) {
+    let ctx = egui::Context::default();
+    let mut g = make_graph(10000, 20000, payload);
+    for _ in 0..WARM_UP_FRAMES {
+        frame(&ctx, &mut g, culling, fit);
+    }
+    group.bench_function(name, |b| b.iter(|| frame(&ctx, &mut g, culling, fit)));
+}
+
+fn bench_draw(c: &mut Criterion) {
+    let mut group = c.benchmark_group("draw_frame");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_millis(600));
+    group.warm_up_time(Duration::from_millis(200));
+
+    // Culling off: measures edge drawing alone.
+    bench_frames(&mut group, "n10000_m20000_unit_payload", &(), false, true);
+    // Payloads used to be cloned twice per edge while drawing.
+    let heavy = vec![0u64; 512];
+    bench_frames(
+        &mut group,
+        "n10000_m20000_4kib_payload",
+        &heavy,
+        false,
+        true,
+    );
+
+    // Zoomed in, with and without viewport culling.
+    bench_frames(
+        &mut group,
+        "n10000_m20000_zoomed_culling_off",
+        &(),
+        false,
+        false,
+    );
+    bench_frames(
+        &mut group,
+        "n10000_m20000_zoomed_culling_on",
+        &(),
+        true,
+        false,
+    );
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().configure_from_args();
+    targets = bench_draw
+}
+criterion_main!(benches);
@@ -105,9 +105,98 @@ fn bench_fr_step(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_fr_repulsion_modes(c: &mut Criterion) {
+    let view = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1200.0, 800.0));
+    let mut group = c.benchmark_group("fr_repulsion_exact_vs_barnes_hut");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_millis(600));
+    group.warm_up_time(Duration::from_millis(200));
+
+    for (name, barnes_hut) in [("exact", false), ("barnes_hut", true)] {
+        group.bench_function(format!("n10000_m20000_steps1_{name}"), |b| {
+            b.iter_batched(
+                || {
+                    let g = make_graph(10000, 20000);
+                    let alg = FruchtermanReingold::from_state(FruchtermanReingoldState {
+                        barnes_hut,
+                        ..FruchtermanReingoldState::default()
+                    });
+                    (g, alg)
+                },
+                |(mut g, mut alg)| {
+                    alg.step(&mut g, view);
+                    black_box(g);
+                    black_box(alg);
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+/// Same FR step on a single-threaded rayon pool (sequential path) and on the global pool.
+#[cfg(feature = "parallel")]
+fn bench_fr_parallel(c: &mut Criterion) {
+    let view = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1200.0, 800.0));
+    let mut group = c.benchmark_group("fr_sequential_vs_parallel");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_millis(600));
+    group.warm_up_time(Duration::from_millis(200));
+
+    let single = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .expect("failed to build single-threaded pool");
+
+    for barnes_hut in [false, true] {
+        let mode = if barnes_hut { "barnes_hut" } else { "exact" };
+        let setup = || {
+            let g = make_graph(10000, 20000);
+            let alg = FruchtermanReingold::from_state(FruchtermanReingoldState {
+                barnes_hut,
+                ..FruchtermanReingoldState::default()
+            });
+            (g, alg)
+        };
+
+        group.bench_function(format!("n10000_m20000_steps1_{mode}_sequential"), |b| {
+            b.iter_batched(
+                setup,
+                |(mut g, mut alg)| {
+                    single.install(|| alg.step(&mut g, view));
+                    black_box(g);
+                    black_box(alg);
+                },
+                BatchSize::SmallInput,
+            );
+        });
+
+        group.bench_function(format!("n10000_m20000_steps1_{mode}_parallel"), |b| {
+            b.iter_batched(
+                setup,
+                |(mut g, mut alg)| {
+                    alg.step(&mut g, view);
+                    black_box(g);
+                    black_box(alg);
+                },
+                BatchSize::SmallInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "parallel"))]
+criterion_group! {
+    name = benches;
+    config = Criterion::default().configure_from_args();
+    targets = bench_fr_step, bench_fr_repulsion_modes
+}
+#[cfg(feature = "parallel")]
 criterion_group! {
     name = benches;
     config = Criterion::default().configure_from_args();
-    targets = bench_fr_step
+    targets = bench_fr_step, bench_fr_repulsion_modes, bench_fr_parallel
 }
 criterion_main!(benches);
@@ -6,7 +6,10 @@ use egui::{
 };
 use petgraph::{stable_graph::IndexType, EdgeType};
 
-use crate::{draw::DrawContext, elements::EdgeProps, node_size, DisplayEdge, DisplayNode, Node};
+use crate::{
+    draw::DrawContext, elements::EdgeProps, node_size, DisplayEdge, DisplayNode, LevelOfDetail,
+    Node,
+};
 
 use super::edge_shape::{EdgeShapeBuilder, TipProps};
 
@@ -65,10 +68,23 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, I
         end: &Node<N, E, Ty, Ix, D>,
         ctx: &DrawContext,
     ) -> Vec<egui::Shape> {
-        let label_visible = ctx.style.labels_always || self.selected;
+        let label_visible = ctx.labels_always() || self.selected;
         let color = self.current_color(ctx);
         let stroke = self.current_stroke(ctx, color);
 
+        if ctx.lod == LevelOfDetail::Minimal {
+            if start.id() == end.id() {
+                return vec![];
+            }
+            return vec![Shape::line_segment(
+                [
+                    ctx.meta.canvas_to_screen_pos(start.location()),
+                    ctx.meta.canvas_to_screen_pos(end.location()),
+                ],
+                Stroke::new(ctx.meta.canvas_to_screen_size(stroke.width), stroke.color),
+            )];
+        }
+
         if start.id() == end.id() {
             return self.loop_shapes(start, ctx, stroke, color, label_visible);
         }
@@ -233,7 +249,7 @@ impl DefaultEdgeShape {
         let mut res = vec![];
         let color = self.current_color(ctx);
         let stroke = self.current_stroke(ctx, color);
-        let label_visible = ctx.style.labels_always || self.selected;
+        let label_visible = ctx.labels_always() || self.selected;
         let start_connector_point = start.display().closest_boundary_point(dir);
         let end_connector_point = end.display().closest_boundary_point(-dir);
         let mut builder = EdgeShapeBuilder::new(stroke)
@@ -288,7 +304,7 @@ impl DefaultEdgeShape {
         let mut res = vec![];
         let color = self.current_color(ctx);
         let stroke = self.current_stroke(ctx, color);
-        let label_visible = ctx.style.labels_always || self.selected;
+        let label_visible = ctx.labels_always() || self.selected;
         let start_connector_point = start.display().closest_boundary_point(dir);
         let end_connector_point = end.display().closest_boundary_point(-dir);
         let mut builder = EdgeShapeBuilder::new(stroke)
@@ -4,7 +4,7 @@ use egui::{
 };
 use petgraph::{stable_graph::IndexType, EdgeType};
 
-use crate::{draw::drawer::DrawContext, DisplayNode, NodeProps};
+use crate::{draw::drawer::DrawContext, DisplayNode, LevelOfDetail, NodeProps};
 
 /// This is the default node shape which is used to display nodes in the graph.
 ///
@@ -55,6 +55,13 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
         let circle_center = ctx.meta.canvas_to_screen_pos(self.pos);
         let circle_radius = ctx.meta.canvas_to_screen_size(self.radius);
         let color = self.effective_color(ctx);
+        if ctx.lod == LevelOfDetail::Minimal {
+            return vec![Shape::circle_filled(
+                circle_center,
+                circle_radius.min(1.0),
+                color,
+            )];
+        }
         let stroke = self.effective_stroke(ctx);
 
         res.push(
@@ -67,7 +74,7 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
             .into(),
         );
 
-        if !(ctx.style.labels_always || self.selected || self.dragged || self.hovered) {
+        if !(ctx.labels_always() || self.selected || self.dragged || self.hovered) {
             return res;
         }
 
@@ -192,4 +199,44 @@ mod test {
             Pos2::new(0.0, 10.0)
         ));
     }
+
+    #[test]
+    fn follows_level_of_detail() {
+        let ctx = egui::Context::default();
+        let painter = egui::Painter::new(
+            ctx.clone(),
+            egui::LayerId::background(),
+            egui::Rect::EVERYTHING,
+        );
+        let style = crate::SettingsStyle::new()
+            .with_labels_always(true)
+            .with_labels_min_zoom(0.5)
+            .with_full_detail_min_zoom(0.1);
+        let mut node = DefaultNodeShape::from(crate::Node::<(), ()>::new(()).props().clone());
+        let mut shapes = |zoom: f32| {
+            let mut meta = crate::metadata::MetadataFrame::default();
+            meta.zoom = zoom;
+            let draw_ctx = DrawContext {
+                ctx: &ctx,
+                painter: &painter,
+                style: &style,
+                is_directed: true,
+                meta: &meta,
+                lod: style.lod(zoom),
+            };
+            DisplayNode::<(), (), petgraph::Directed, petgraph::stable_graph::DefaultIx>::shapes(
+                &mut node, &draw_ctx,
+            )
+        };
+
+        assert_eq!(style.lod(1.0), LevelOfDetail::Full);
+        // Labels are hidden despite `labels_always`, only the circle is left.
+        let no_labels = shapes(0.2);
+        assert_eq!(no_labels.len(), 1);
+        assert!(matches!(&no_labels[0], Shape::Circle(c) if c.radius == 1.0));
+        // A point instead of the 5 unit circle.
+        let minimal = shapes(0.05);
+        assert_eq!(minimal.len(), 1);
+        assert!(matches!(&minimal[0], Shape::Circle(c) if c.radius < 0.3 && c.stroke.is_empty()));
+    }
 }
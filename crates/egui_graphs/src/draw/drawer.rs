@@ -1,18 +1,23 @@
 use std::marker::PhantomData;
 
-use egui::{Context, Painter, Shape};
+use egui::{Context, Painter, Rect, Shape};
 use petgraph::graph::IndexType;
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
 use petgraph::EdgeType;
 
 use crate::{
     layouts::{Layout, LayoutState},
     metadata::MetadataFrame,
-    settings::SettingsStyle,
+    settings::{LevelOfDetail, SettingsStyle},
     Graph,
 };
 
 use super::{DisplayEdge, DisplayNode};
 
+/// Screen space margin around the widget within which nodes and edges are still drawn, so
+/// labels and other shapes reaching past their bounds do not pop in at the border.
+const CULLING_MARGIN: f32 = 64.0;
+
 /// Contains all the data about current widget state which is needed for custom drawing functions.
 pub struct DrawContext<'a> {
     pub ctx: &'a Context,
@@ -20,6 +25,17 @@ pub struct DrawContext<'a> {
     pub style: &'a SettingsStyle,
     pub is_directed: bool,
     pub meta: &'a MetadataFrame,
+    /// Level of detail for the current zoom. Custom displays may simplify their shapes
+    /// accordingly.
+    pub lod: LevelOfDetail,
+}
+
+impl DrawContext<'_> {
+    /// Whether labels of not interacted elements are shown, combining
+    /// [`SettingsStyle::with_labels_always`] with the level of detail.
+    pub fn labels_always(&self) -> bool {
+        self.style.labels_always && self.lod == LevelOfDetail::Full
+    }
 }
 
 pub(crate) struct Drawer<'a, N, E, Ty, Ix, Nd, Ed, S, L>
@@ -63,34 +79,38 @@ where
     /// Renders the graph for the current frame.
     ///
     /// Order matters:
-    /// 1. `update_nodes` syncs each node's display object from its props so edge geometry
-    ///    (which reads node display boundary points) uses fresh positions / sizes.
-    /// 2. `draw_edges` builds edge shapes using the updated node display state.
-    /// 3. `draw_nodes` paints nodes (non‑selected first) while deferring highlighted ones.
-    /// 4. `draw_delayed` paints deferred (selected / dragged) shapes on top.
+    /// 1. [`Graph::update_spatial_index`] catches up with the nodes and edges changed by
+    ///    interactions since `GraphView` refreshed it, syncing their displays, so edge
+    ///    geometry (which reads node display boundary points) uses fresh positions / sizes.
+    /// 2. `visible` picks the nodes and edges near the widget from the spatial index.
+    /// 3. `draw_edges` builds edge shapes using the updated node display state.
+    /// 4. `draw_nodes` paints nodes (non‑selected first) while deferring highlighted ones.
+    /// 5. `draw_delayed` paints deferred (selected / dragged) shapes on top.
     pub(crate) fn draw(mut self) {
-        self.update_nodes();
-        self.draw_edges();
-        self.draw_nodes();
+        self.g.update_spatial_index();
+        let (nodes, edges) = self.visible();
+        self.draw_edges(edges);
+        self.draw_nodes(nodes);
         self.draw_delayed();
     }
 
-    /// Synchronizes node display state with their current props without emitting shapes.
-    ///
-    /// This is a separate pass so edges can rely on `DisplayNode` geometry (e.g. boundary
-    /// points) already being in sync when computing connector positions.
-    fn update_nodes(&mut self) {
-        self.g
-            .g_mut()
-            .node_indices()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .for_each(|idx| {
-                if let Some(n) = self.g.node_mut(idx) {
-                    let props = n.props().clone();
-                    n.display_mut().update(&props);
-                }
-            });
+    /// Nodes and edges whose bounds intersect the painter clip rect grown by
+    /// [`CULLING_MARGIN`], or all of them if viewport culling is disabled.
+    fn visible(&self) -> (Vec<NodeIndex<Ix>>, Vec<EdgeIndex<Ix>>) {
+        if !self.ctx.style.viewport_culling {
+            let g = self.g.g();
+            return (g.node_indices().collect(), g.edge_indices().collect());
+        }
+
+        let screen = self.ctx.painter.clip_rect().expand(CULLING_MARGIN);
+        let canvas = Rect::from_min_max(
+            self.ctx.meta.screen_to_canvas_pos(screen.min),
+            self.ctx.meta.screen_to_canvas_pos(screen.max),
+        );
+        (
+            self.g.nodes_in_canvas_rect(canvas),
+            self.g.edges_in_canvas_rect(canvas),
+        )
     }
 
     fn draw_delayed(&mut self) {
@@ -99,57 +119,54 @@ where
         });
     }
 
-    fn draw_nodes(&mut self) {
-        self.g
-            .g_mut()
-            .node_indices()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .for_each(|idx| {
-                let n = self.g.node_mut(idx).unwrap();
-                let shapes = n.display_mut().shapes(self.ctx);
-
-                if n.selected() || n.dragged() {
-                    for s in shapes {
-                        self.delayed.push(s);
-                    }
-                } else {
-                    for s in shapes {
-                        self.ctx.painter.add(s);
-                    }
+    fn draw_nodes(&mut self, nodes: Vec<NodeIndex<Ix>>) {
+        for idx in nodes {
+            let n = &mut self.g.g_mut_for_draw()[idx];
+            let shapes = n.display_mut().shapes(self.ctx);
+
+            if n.selected() || n.dragged() {
+                self.delayed.extend(shapes);
+            } else {
+                for s in shapes {
+                    self.ctx.painter.add(s);
                 }
-            });
+            }
+        }
     }
 
-    fn draw_edges(&mut self) {
-        self.g
-            .g_mut()
-            .edge_indices()
-            .collect::<Vec<_>>()
-            .into_iter()
-            .for_each(|idx| {
-                let (idx_start, idx_end) = self.g.edge_endpoints(idx).unwrap();
-
-                // FIXME: too costly to clone nodes for every edge
-                let start = self.g.node(idx_start).cloned().unwrap();
-                let end = self.g.node(idx_end).cloned().unwrap();
-
-                let e = self.g.edge_mut(idx).unwrap();
-                let props = e.props().clone();
-
-                let display = e.display_mut();
-                display.update(&props);
-                let shapes = display.shapes(&start, &end, self.ctx);
-
-                if e.selected() {
-                    for s in shapes {
-                        self.delayed.push(s);
-                    }
-                } else {
-                    for s in shapes {
-                        self.ctx.painter.add(s);
-                    }
+    /// Builds edge shapes with both endpoints borrowed from the graph.
+    ///
+    /// Each edge display is swapped out of the graph while it builds its shapes, so the
+    /// graph is only borrowed immutably meanwhile. A single display cloned per frame stands
+    /// in for it.
+    fn draw_edges(&mut self, edges: Vec<EdgeIndex<Ix>>) {
+        let Some(first) = edges.first() else {
+            return;
+        };
+        let mut spare = self.g.edge(*first).unwrap().display().clone();
+
+        for idx in edges {
+            let e = &mut self.g.g_mut_for_draw()[idx];
+            let mut display = std::mem::replace(e.display_mut(), spare);
+
+            let (idx_start, idx_end) = self.g.edge_endpoints(idx).unwrap();
+            let e = self.g.edge(idx).unwrap();
+            display.update(e.props());
+            let shapes = display.shapes(
+                self.g.node(idx_start).unwrap(),
+                self.g.node(idx_end).unwrap(),
+                self.ctx,
+            );
+
+            if e.selected() {
+                self.delayed.extend(shapes);
+            } else {
+                for s in shapes {
+                    self.ctx.painter.add(s);
                 }
-            });
+            }
+
+            spare = std::mem::replace(self.g.g_mut_for_draw()[idx].display_mut(), display);
+        }
     }
 }
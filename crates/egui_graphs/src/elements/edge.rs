@@ -84,6 +84,11 @@ impl<
         self.id = Some(id);
     }
 
+    /// Updates the display from the props without cloning them.
+    pub(crate) fn sync_display(&mut self) {
+        self.display.update(&self.props);
+    }
+
     pub fn order(&self) -> usize {
         self.props.order
     }
@@ -21,9 +21,20 @@ where
     pub selected: bool,
     pub dragged: bool,
     pub hovered: bool,
+    /// Pinned nodes keep their location: layouts skip them, user dragging still moves them.
+    #[serde(default)]
+    pub pinned: bool,
 
     color: Option<Color32>,
     location: Pos2,
+    /// Whether the location was ever assigned, by the user or by a layout.
+    /// Nodes saved before this field existed count as placed.
+    #[serde(default = "yes")]
+    location_set: bool,
+}
+
+fn yes() -> bool {
+    true
 }
 
 impl<N> NodeProps<N>
@@ -34,6 +45,11 @@ where
         self.location
     }
 
+    /// Returns `false` until a location is assigned with [`Node::set_location`].
+    pub fn location_set(&self) -> bool {
+        self.location_set
+    }
+
     pub fn color(&self) -> Option<Color32> {
         self.color
     }
@@ -102,11 +118,13 @@ where
         let props = NodeProps {
             payload,
             location: Pos2::default(),
+            location_set: false,
             color: Option::default(),
             label: String::default(),
             selected: bool::default(),
             dragged: bool::default(),
             hovered: bool::default(),
+            pinned: bool::default(),
         };
 
         Node::new_with_props(props)
@@ -145,6 +163,11 @@ where
         self.id = Some(id);
     }
 
+    /// Updates the display from the props without cloning them.
+    pub(crate) fn sync_display(&mut self) {
+        self.display.update(&self.props);
+    }
+
     pub fn payload(&self) -> &N {
         &self.props.payload
     }
@@ -165,8 +188,15 @@ where
         self.props.location()
     }
 
+    /// Returns `false` until a location is assigned with [`Node::set_location`], e.g. for
+    /// freshly added nodes that no layout has placed yet.
+    pub fn location_set(&self) -> bool {
+        self.props.location_set()
+    }
+
     pub fn set_location(&mut self, loc: Pos2) {
         self.props.location = loc;
+        self.props.location_set = true;
     }
 
     pub fn selected(&self) -> bool {
@@ -193,6 +223,16 @@ where
         self.props.hovered = hovered;
     }
 
+    pub fn pinned(&self) -> bool {
+        self.props.pinned
+    }
+
+    /// Pins the node in place. Layouts leave pinned nodes where they are, while they
+    /// still take part in the forces acting on other nodes.
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.props.pinned = pinned;
+    }
+
     pub fn label(&self) -> String {
         self.props.label.clone()
     }
@@ -201,3 +241,18 @@ where
         self.props.label = label;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn props_saved_without_location_set_count_as_placed() {
+        let json = r#"{"payload":null,"label":"","selected":false,"dragged":false,"hovered":false,"color":null,"location":{"x":1.0,"y":2.0}}"#;
+        let props: NodeProps<()> = serde_json::from_str(json).unwrap();
+        assert!(props.location_set());
+        assert_eq!(props.location(), Pos2::new(1.0, 2.0));
+
+        assert!(!Node::<(), ()>::new(()).location_set());
+    }
+}
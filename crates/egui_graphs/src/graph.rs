@@ -1,6 +1,7 @@
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use egui::{Pos2, Rect};
+use egui::{Pos2, Rect, Vec2};
 use petgraph::stable_graph::DefaultIx;
 use petgraph::Directed;
 
@@ -16,7 +17,11 @@ use crate::draw::{DisplayEdge, DisplayNode};
 use crate::{
     default_edge_transform, default_node_transform, to_graph, DefaultEdgeShape, DefaultNodeShape,
 };
-use crate::{metadata::MetadataFrame, Edge, Node};
+use crate::{
+    metadata::MetadataFrame,
+    spatial_index::{Grid, SpatialIndex},
+    Edge, Node,
+};
 
 type StableGraphType<N, E, Ty, Ix, Dn, De> =
     StableGraph<Node<N, E, Ty, Ix, Dn>, Edge<N, E, Ty, Ix, Dn, De>, Ty, Ix>;
@@ -47,6 +52,24 @@ pub struct Graph<
     hovered_node: Option<NodeIndex<Ix>>,
 
     bounds: Rect,
+
+    /// Bumped by [`Self::g_mut`], after which the spatial index is rebuilt in full.
+    #[serde(skip)]
+    generation: u64,
+    /// Changes whenever nodes or edges may have been added or removed, see
+    /// [`Self::structure`].
+    #[serde(skip, default = "next_structure")]
+    structure: u64,
+    #[serde(skip)]
+    index: SpatialIndex,
+}
+
+/// Source of [`Graph::structure`] values. Shared by all graphs, so a value seen on one
+/// graph never matches another one.
+static STRUCTURE: AtomicU64 = AtomicU64::new(0);
+
+fn next_structure() -> u64 {
+    STRUCTURE.fetch_add(1, Ordering::Relaxed)
 }
 
 impl<N, E, Ty, Ix, Dn, De> From<&StableGraph<N, E, Ty, Ix>> for Graph<N, E, Ty, Ix, Dn, De>
@@ -80,48 +103,248 @@ where
             dragged_node: Option::default(),
             hovered_node: Option::default(),
             bounds: Rect::from_min_max(Pos2::ZERO, Pos2::ZERO),
+            generation: 0,
+            structure: next_structure(),
+            index: SpatialIndex::default(),
         }
     }
 
-    /// Finds node by position. Can be optimized by using a spatial index like quad-tree if needed.
+    /// Finds node by position. Returns the first of [`Self::nodes_by_screen_pos`].
     pub fn node_by_screen_pos(
         &self,
         meta: &MetadataFrame,
         screen_pos: Pos2,
     ) -> Option<NodeIndex<Ix>> {
-        let pos_in_graph = meta.screen_to_canvas_pos(screen_pos);
-        for (idx, node) in self.nodes_iter() {
-            let display = node.display();
-            if display.is_inside(pos_in_graph) {
-                return Some(idx);
-            }
-        }
-        None
+        self.nodes_by_screen_pos(meta, screen_pos)
+            .into_iter()
+            .next()
     }
 
-    /// Finds edge by position.
-    #[allow(clippy::missing_panics_doc)] // TODO: add panics doc
+    /// Finds edge by position. Returns the first of [`Self::edges_by_screen_pos`].
     pub fn edge_by_screen_pos(
         &self,
         meta: &MetadataFrame,
         screen_pos: Pos2,
     ) -> Option<EdgeIndex<Ix>> {
-        let pos_in_graph = meta.screen_to_canvas_pos(screen_pos);
-        for (idx, e) in self.edges_iter() {
-            let Some((idx_start, idx_end)) = self.g.edge_endpoints(e.id()) else {
+        self.edges_by_screen_pos(meta, screen_pos)
+            .into_iter()
+            .next()
+    }
+
+    /// Finds all nodes at position, in index order.
+    ///
+    /// Candidates come from the spatial index, see [`Self::update_spatial_index`], and are
+    /// confirmed with [`DisplayNode::is_inside`].
+    pub fn nodes_by_screen_pos(
+        &self,
+        meta: &MetadataFrame,
+        screen_pos: Pos2,
+    ) -> Vec<NodeIndex<Ix>> {
+        let pos = meta.screen_to_canvas_pos(screen_pos);
+        self.node_candidates(|grid| grid.at(pos))
+            .into_iter()
+            .filter(|&idx| self.g[idx].display().is_inside(pos))
+            .collect()
+    }
+
+    /// Finds all edges at position, in index order.
+    ///
+    /// Candidates come from the spatial index, see [`Self::update_spatial_index`], and are
+    /// confirmed with [`DisplayEdge::is_inside`].
+    pub fn edges_by_screen_pos(
+        &self,
+        meta: &MetadataFrame,
+        screen_pos: Pos2,
+    ) -> Vec<EdgeIndex<Ix>> {
+        let pos = meta.screen_to_canvas_pos(screen_pos);
+        self.edge_candidates(|grid| grid.at(pos))
+            .into_iter()
+            .filter(|&idx| {
+                let (start, end) = self.g.edge_endpoints(idx).unwrap();
+                self.g[idx]
+                    .display()
+                    .is_inside(&self.g[start], &self.g[end], pos)
+            })
+            .collect()
+    }
+
+    /// Finds all nodes whose bounding box intersects `rect` given in canvas coordinates, in
+    /// index order.
+    pub fn nodes_in_canvas_rect(&self, rect: Rect) -> Vec<NodeIndex<Ix>> {
+        self.node_candidates(|grid| grid.intersecting(rect))
+            .into_iter()
+            .filter(|&idx| node_bounds(&self.g[idx]).intersects(rect))
+            .collect()
+    }
+
+    /// Finds all edges whose bounding box intersects `rect` given in canvas coordinates, in
+    /// index order.
+    pub fn edges_in_canvas_rect(&self, rect: Rect) -> Vec<EdgeIndex<Ix>> {
+        self.edge_candidates(|grid| grid.intersecting(rect))
+            .into_iter()
+            .filter(|&idx| self.edge_bounds(idx).is_some_and(|b| b.intersects(rect)))
+            .collect()
+    }
+
+    /// Updates the spatial index used by the hit-testing methods to the current node and
+    /// edge shapes, syncing their displays on the way.
+    ///
+    /// Only nodes and edges changed since the last call through [`Self::node_mut`],
+    /// [`Self::edge_mut`] or the add and remove methods are refreshed, together with the
+    /// edges of changed nodes. Any use of [`Self::g_mut`] makes the next call rebuild the
+    /// whole index.
+    ///
+    /// [`super::GraphView`] calls this every frame before handling interactions. Until the
+    /// next call changed nodes and edges are always returned as candidates, and after
+    /// [`Self::g_mut`] every node and edge is.
+    pub fn update_spatial_index(&mut self) {
+        if !self.index_swept() {
+            self.sweep_spatial_index();
+            return;
+        }
+        if self.index.dirty_nodes.is_empty() && self.index.dirty_edges.is_empty() {
+            return;
+        }
+
+        let dirty_nodes = std::mem::take(&mut self.index.dirty_nodes);
+        let mut dirty_edges = std::mem::take(&mut self.index.dirty_edges);
+        for id in dirty_nodes {
+            let idx = NodeIndex::new(id);
+            let Some(n) = self.g.node_weight_mut(idx) else {
+                self.index.nodes.remove(id);
+                continue;
+            };
+            n.sync_display();
+            self.index.nodes.set(id, node_bounds(n));
+            dirty_edges.extend(self.incident_edges(idx));
+        }
+        for id in dirty_edges {
+            let idx = EdgeIndex::new(id);
+            let Some(e) = self.g.edge_weight_mut(idx) else {
+                self.index.edges.remove(id);
                 continue;
             };
-            let start = self.g.node_weight(idx_start).unwrap();
-            let end = self.g.node_weight(idx_end).unwrap();
-            if e.display().is_inside(start, end, pos_in_graph) {
-                return Some(idx);
+            e.sync_display();
+            if let Some(bounds) = self.edge_bounds(idx) {
+                self.index.edges.set(id, bounds);
             }
         }
+    }
+
+    /// Syncs every display and moves every entry of the index.
+    fn sweep_spatial_index(&mut self) {
+        self.g.node_weights_mut().for_each(Node::sync_display);
+        self.g.edge_weights_mut().for_each(Edge::sync_display);
+        for (idx, n) in self.g.node_references() {
+            self.index.nodes.set(idx.index(), node_bounds(n));
+        }
+        for idx in self.g.edge_indices() {
+            if let Some(bounds) = self.edge_bounds(idx) {
+                self.index.edges.set(idx.index(), bounds);
+            }
+        }
+
+        let g = &self.g;
+        self.index
+            .nodes
+            .retain(|i| g.node_weight(NodeIndex::new(i)).is_some());
+        self.index
+            .edges
+            .retain(|i| g.edge_weight(EdgeIndex::new(i)).is_some());
+        self.index.dirty_nodes.clear();
+        self.index.dirty_edges.clear();
+        self.index.swept = Some(self.generation);
+    }
+
+    /// Whether the index was fully built since the last [`Self::g_mut`].
+    fn index_swept(&self) -> bool {
+        self.index.swept == Some(self.generation)
+    }
 
-        None
+    fn incident_edges(&self, idx: NodeIndex<Ix>) -> impl Iterator<Item = usize> + '_ {
+        self.g
+            .edges_directed(idx, Direction::Outgoing)
+            .chain(self.g.edges_directed(idx, Direction::Incoming))
+            .map(|e| e.id().index())
+    }
+
+    fn mark_node(&mut self, idx: NodeIndex<Ix>) {
+        if self.index_swept() {
+            self.index.dirty_nodes.insert(idx.index());
+        }
+    }
+
+    fn mark_edge(&mut self, idx: EdgeIndex<Ix>) {
+        if self.index_swept() {
+            self.index.dirty_edges.insert(idx.index());
+        }
+    }
+
+    /// Nodes returned by `query` on the index plus the nodes changed since the last
+    /// refresh, or all nodes if the index needs a full rebuild.
+    fn node_candidates(&self, query: impl FnOnce(&Grid) -> Vec<usize>) -> Vec<NodeIndex<Ix>> {
+        if !self.index_swept() {
+            return self.g.node_indices().collect();
+        }
+        let mut ids = query(&self.index.nodes);
+        if !self.index.dirty_nodes.is_empty() {
+            ids.extend(&self.index.dirty_nodes);
+            ids.sort_unstable();
+            ids.dedup();
+        }
+        ids.into_iter()
+            .map(NodeIndex::new)
+            .filter(|&idx| self.g.node_weight(idx).is_some())
+            .collect()
+    }
+
+    /// Edges returned by `query` on the index plus the edges changed since the last
+    /// refresh, directly or through an endpoint, or all edges if the index needs a full
+    /// rebuild.
+    fn edge_candidates(&self, query: impl FnOnce(&Grid) -> Vec<usize>) -> Vec<EdgeIndex<Ix>> {
+        if !self.index_swept() {
+            return self.g.edge_indices().collect();
+        }
+        let mut ids = query(&self.index.edges);
+        if !self.index.dirty_nodes.is_empty() || !self.index.dirty_edges.is_empty() {
+            ids.extend(&self.index.dirty_edges);
+            for &id in &self.index.dirty_nodes {
+                ids.extend(self.incident_edges(NodeIndex::new(id)));
+            }
+            ids.sort_unstable();
+            ids.dedup();
+        }
+        ids.into_iter()
+            .map(EdgeIndex::new)
+            .filter(|&idx| self.g.edge_weight(idx).is_some())
+            .collect()
+    }
+
+    /// Bounding box of the edge: its endpoint nodes, the segment between their locations
+    /// and [`DisplayEdge::extra_bounds`], padded by [`EDGE_HIT_MARGIN`].
+    fn edge_bounds(&self, idx: EdgeIndex<Ix>) -> Option<Rect> {
+        let (start_idx, end_idx) = self.g.edge_endpoints(idx)?;
+        let (start, end) = (&self.g[start_idx], &self.g[end_idx]);
+        let mut bounds = node_bounds(start)
+            .union(node_bounds(end))
+            .union(Rect::from_two_pos(start.location(), end.location()));
+        if let Some((min, max)) = self.g[idx].display().extra_bounds(start, end) {
+            bounds = bounds.union(Rect::from_min_max(min, max));
+        }
+        Some(bounds.expand(EDGE_HIT_MARGIN))
     }
 
+    /// Mutable access to the underlying graph. The spatial index can not tell what changed
+    /// through it and is rebuilt in full by the next [`Self::update_spatial_index`].
     pub fn g_mut(&mut self) -> &mut StableGraphType<N, E, Ty, Ix, Dn, De> {
+        self.generation = self.generation.wrapping_add(1);
+        self.structure = next_structure();
+        &mut self.g
+    }
+
+    /// Mutable access for the draw pass which only touches display internals, so the
+    /// spatial index is kept.
+    pub(crate) fn g_mut_for_draw(&mut self) -> &mut StableGraphType<N, E, Ty, Ix, Dn, De> {
         &mut self.g
     }
 
@@ -129,6 +352,13 @@ where
         &self.g
     }
 
+    /// Value that changes whenever nodes or edges are added or removed, or the graph is
+    /// accessed through [`Self::g_mut`]. Lets layouts keep data derived from the graph
+    /// structure between frames.
+    pub(crate) fn structure(&self) -> u64 {
+        self.structure
+    }
+
     /// Adds node to graph setting default location and default label values
     #[allow(clippy::missing_panics_doc)] // TODO: add panics doc
     pub fn add_node(&mut self, payload: N) -> NodeIndex<Ix> {
@@ -149,6 +379,8 @@ where
         graph_node.set_id(idx);
 
         node_transform(graph_node);
+        self.mark_node(idx);
+        self.structure = next_structure();
 
         idx
     }
@@ -191,9 +423,19 @@ where
             self.remove_edges_between(*n, idx);
         }
 
+        self.index.nodes.remove(idx.index());
+        self.index.dirty_nodes.remove(&idx.index());
+        self.structure = next_structure();
         self.g.remove_node(idx)
     }
 
+    /// Drops a removed edge from the spatial index.
+    fn forget_edge(&mut self, idx: EdgeIndex<Ix>) {
+        self.index.edges.remove(idx.index());
+        self.index.dirty_edges.remove(&idx.index());
+        self.structure = next_structure();
+    }
+
     /// Removes all edges between start and end node. Returns removed edges count.
     #[allow(clippy::missing_panics_doc)] // TODO: add panics doc
     pub fn remove_edges_between(&mut self, start: NodeIndex<Ix>, end: NodeIndex<Ix>) -> usize {
@@ -209,6 +451,7 @@ where
         let mut removed = 0;
         for e in &idxs {
             self.g.remove_edge(*e).unwrap();
+            self.forget_edge(*e);
             removed += 1;
         }
 
@@ -261,6 +504,7 @@ where
         }
 
         let idx = self.g.add_edge(start, end, Edge::new(payload));
+        self.structure = next_structure();
         let e = self.g.edge_weight_mut(idx).unwrap();
 
         e.set_id(idx);
@@ -297,12 +541,15 @@ where
         }
 
         if increase_order {
-            for id in siblings_ids {
+            for &id in &siblings_ids {
                 if let Some(edge) = self.g.edge_weight_mut(id) {
                     edge.set_order(edge.order() + 1);
                 }
             }
         }
+        for id in siblings_ids {
+            self.mark_edge(id);
+        }
 
         idx
     }
@@ -314,6 +561,7 @@ where
         let order = self.g.edge_weight(idx)?.order();
 
         let payload = self.g.remove_edge(idx)?;
+        self.forget_edge(idx);
 
         let siblings = self
             .g
@@ -328,6 +576,7 @@ where
                 continue;
             }
             self.g.edge_weight_mut(*s_idx)?.set_order(sibling_order - 1);
+            self.mark_edge(*s_idx);
         }
 
         Some(payload)
@@ -369,10 +618,12 @@ where
     }
 
     pub fn node_mut(&mut self, i: NodeIndex<Ix>) -> Option<&mut Node<N, E, Ty, Ix, Dn>> {
+        self.mark_node(i);
         self.g.node_weight_mut(i)
     }
 
     pub fn edge_mut(&mut self, i: EdgeIndex<Ix>) -> Option<&mut Edge<N, E, Ty, Ix, Dn, De>> {
+        self.mark_edge(i);
         self.g.edge_weight_mut(i)
     }
 
@@ -441,6 +692,23 @@ where
     }
 }
 
+/// Padding of edge bounding boxes in canvas units, covers the hit tolerance of thick strokes.
+const EDGE_HIT_MARGIN: f32 = 10.0;
+
+/// Bounding box of the node shape, taken from its boundary points along both axes.
+fn node_bounds<N, E, Ty, Ix, Dn>(n: &Node<N, E, Ty, Ix, Dn>) -> Rect
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+{
+    let d = n.display();
+    let points = [Vec2::X, -Vec2::X, Vec2::Y, -Vec2::Y].map(|dir| d.closest_boundary_point(dir));
+    Rect::from_points(&points)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -482,4 +750,78 @@ mod tests {
         let o4 = g.edge(e4).unwrap().order();
         assert_eq!(o4, 2, "Third A->B edge should get order 2");
     }
+
+    /// Moves node displays to their locations like the draw pass does.
+    fn sync_displays(g: &mut Graph) {
+        for n in g.g_mut().node_weights_mut() {
+            let props = n.props().clone();
+            DisplayNode::<(), (), Directed, DefaultIx>::update(n.display_mut(), &props);
+        }
+    }
+
+    #[test]
+    fn hit_tests_return_all_hits_and_follow_moves() {
+        let mut g: Graph = Graph::new(StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0.0, 0.0));
+        let b = g.add_node_with_location((), Pos2::new(4.0, 0.0));
+        let c = g.add_node_with_location((), Pos2::new(400.0, 0.0));
+        let e = g.add_edge(b, c, ());
+        let meta = MetadataFrame::default();
+        sync_displays(&mut g);
+
+        // Not indexed yet: falls back to a linear scan.
+        assert_eq!(
+            g.nodes_by_screen_pos(&meta, Pos2::new(2.0, 0.0)),
+            vec![a, b]
+        );
+
+        g.update_spatial_index();
+        assert_eq!(
+            g.nodes_by_screen_pos(&meta, Pos2::new(2.0, 0.0)),
+            vec![a, b]
+        );
+        assert_eq!(g.node_by_screen_pos(&meta, Pos2::new(8.0, 0.0)), Some(b));
+        assert_eq!(g.edges_by_screen_pos(&meta, Pos2::new(200.0, 1.0)), vec![e]);
+        assert_eq!(g.edge_by_screen_pos(&meta, Pos2::new(200.0, 50.0)), None);
+        assert_eq!(
+            g.nodes_in_canvas_rect(Rect::from_min_max(
+                Pos2::new(3.0, -1.0),
+                Pos2::new(500.0, 1.0)
+            )),
+            vec![a, b, c]
+        );
+
+        g.remove_node(a);
+        g.node_mut(c).unwrap().set_location(Pos2::new(0.0, 300.0));
+        g.update_spatial_index();
+        assert!(g.index_swept() && g.index.dirty_nodes.is_empty());
+        assert_eq!(g.nodes_by_screen_pos(&meta, Pos2::new(2.0, 0.0)), vec![b]);
+        assert_eq!(g.nodes_by_screen_pos(&meta, Pos2::new(0.0, 300.0)), vec![c]);
+        assert!(g
+            .nodes_by_screen_pos(&meta, Pos2::new(400.0, 0.0))
+            .is_empty());
+        assert_eq!(g.edges_by_screen_pos(&meta, Pos2::new(2.0, 150.0)), vec![e]);
+    }
+
+    #[test]
+    fn reused_indices_are_reindexed() {
+        let mut g: Graph = Graph::new(StableGraph::default());
+        let a = g.add_node_with_location((), Pos2::new(0.0, 0.0));
+        let b = g.add_node_with_location((), Pos2::new(100.0, 0.0));
+        let meta = MetadataFrame::default();
+        g.update_spatial_index();
+
+        // Same node count as before, and the new node takes over the freed index.
+        g.remove_node(a);
+        let c = g.add_node_with_location((), Pos2::new(500.0, 500.0));
+        assert_eq!(c, a);
+        g.update_spatial_index();
+
+        assert!(g.nodes_by_screen_pos(&meta, Pos2::new(0.0, 0.0)).is_empty());
+        assert_eq!(
+            g.nodes_by_screen_pos(&meta, Pos2::new(500.0, 500.0)),
+            vec![c]
+        );
+        assert_eq!(g.nodes_by_screen_pos(&meta, Pos2::new(100.0, 0.0)), vec![b]);
+    }
 }
@@ -1,14 +1,18 @@
+use std::collections::HashSet;
 use std::marker::PhantomData;
 
 use crate::{
     draw::{drawer::Drawer, DefaultEdgeShape, DefaultNodeShape, DrawContext},
     layouts::{self, Layout, LayoutState},
-    metadata::{reset_metadata, MetadataFrame, MetadataInstance},
+    metadata::{reset_metadata, MetadataFrame, MetadataInstance, SelectionGesture},
     settings::{SettingsInteraction, SettingsNavigation, SettingsStyle},
     DisplayEdge, DisplayNode, Graph,
 };
 
-use egui::{Id, PointerButton, Pos2, Rect, Response, Sense, Ui, Vec2, Widget};
+use egui::{
+    Id, Modifiers, Painter, PointerButton, Pos2, Rect, Response, Sense, Shape, StrokeKind, Ui,
+    Vec2, Widget,
+};
 use web_time::Instant;
 
 use petgraph::{graph::EdgeIndex, stable_graph::DefaultIx};
@@ -175,6 +179,28 @@ struct EffectiveInteraction {
     edge_clicking: bool,
     edge_selection: bool,
     edge_selection_multi: bool,
+    box_selection: bool,
+    lasso_selection: bool,
+}
+
+/// How a box or lasso selection combines with the current selection, picked by the held modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SelectionMode {
+    Replace,
+    Add,
+    Subtract,
+}
+
+impl SelectionMode {
+    fn from_modifiers(modifiers: Modifiers) -> Self {
+        if modifiers.alt {
+            SelectionMode::Subtract
+        } else if modifiers.command {
+            SelectionMode::Add
+        } else {
+            SelectionMode::Replace
+        }
+    }
 }
 
 /// Widget for visualizing and interacting with graphs.
@@ -299,8 +325,9 @@ where
         // Handle node drag before navigation so pan doesn't kick in on the first frame
         // when starting a node drag.
         self.handle_node_drag(&resp, &mut view, eff);
+        self.handle_selection_gesture(ui, &resp, &mut view, eff);
 
-        self.handle_navigation(ui, &resp, &mut view.frame, eff);
+        self.handle_navigation(ui, &resp, &mut view, eff);
         self.handle_click(&resp, &mut view.frame, eff);
 
         // Measure draw time (exclude layout step): start after layout, stop after draw
@@ -317,9 +344,11 @@ where
                 meta: &meta_draw,
                 is_directed: self.g.is_directed(),
                 style: &self.settings_style,
+                lod: self.settings_style.lod(meta_draw.zoom),
             },
         )
         .draw();
+        draw_selection_gesture(ui, &p, &meta_draw, &view.instance);
         let draw_ms = t_draw0.elapsed().as_secs_f32() * 1000.0;
 
         view.frame.last_step_time_ms = step_ms;
@@ -411,6 +440,8 @@ where
             edge_clicking: si.edge_clicking_enabled,
             edge_selection: si.edge_selection_enabled,
             edge_selection_multi: si.edge_selection_multi_enabled,
+            box_selection: si.box_selection_enabled,
+            lasso_selection: si.lasso_selection_enabled,
         };
 
         // Master: dragging -> children
@@ -440,6 +471,12 @@ where
             eff.node_clicking = true;
             eff.hover = true;
         }
+        // Master: box or lasso selection -> children
+        if eff.box_selection || eff.lasso_selection {
+            eff.node_selection = true;
+            eff.node_clicking = true;
+            eff.hover = true;
+        }
 
         eff
     }
@@ -712,6 +749,7 @@ where
         self.g.set_selected_edges(selected_edges);
         self.g.set_dragged_node(dragged);
         self.g.set_bounds(meta.graph_bounds());
+        self.g.update_spatial_index();
     }
 
     /// Fits the graph to the screen if it is the first frame or
@@ -885,8 +923,12 @@ where
             None => None,
         };
 
+        // Shift + drag belongs to box or lasso selection, even when it starts on a node.
+        let selection_gesture = (eff.box_selection || eff.lasso_selection)
+            && (view.instance.selection_gesture.is_some() || resp.ctx.input(|i| i.modifiers.shift));
+
         if resp.is_pointer_button_down_on() {
-            if self.g.dragged_node().is_none() {
+            if self.g.dragged_node().is_none() && !selection_gesture {
                 if let Some(idx) = node_hover_index {
                     self.set_drag_start(idx);
                     self.g.set_dragged_node(Some(idx));
@@ -977,11 +1019,13 @@ where
         &self,
         ui: &Ui,
         resp: &Response,
-        meta: &mut MetadataFrame,
+        view: &mut ViewState,
         eff: EffectiveInteraction,
     ) {
-        self.handle_zoom(ui, resp, meta, eff);
-        self.handle_pan(resp, meta, eff);
+        self.handle_zoom(ui, resp, &mut view.frame, eff);
+        if view.instance.selection_gesture.is_none() {
+            self.handle_pan(resp, &mut view.frame, eff);
+        }
     }
 
     fn handle_zoom(
@@ -1023,6 +1067,185 @@ where
         }
     }
 
+    /// Tracks a `Shift` + primary drag as box or lasso selection and applies it once the
+    /// drag stops.
+    fn handle_selection_gesture(
+        &mut self,
+        ui: &Ui,
+        resp: &Response,
+        view: &mut ViewState,
+        eff: EffectiveInteraction,
+    ) {
+        if !(eff.box_selection || eff.lasso_selection) {
+            view.instance.selection_gesture = None;
+            return;
+        }
+
+        let meta = &view.frame;
+        let modifiers = ui.input(|i| i.modifiers);
+        if view.instance.selection_gesture.is_none() {
+            if !(resp.drag_started_by(PointerButton::Primary)
+                && modifiers.shift
+                && self.g.dragged_node().is_none())
+            {
+                return;
+            }
+            let Some(origin) = ui.input(|i| i.pointer.press_origin()) else {
+                return;
+            };
+            let start = meta.screen_to_canvas_pos(self.local_pos(resp, origin));
+            view.instance.selection_gesture = Some(if eff.lasso_selection {
+                SelectionGesture::Lasso(vec![start])
+            } else {
+                SelectionGesture::Box(start, start)
+            });
+        }
+
+        let Some(gesture) = &mut view.instance.selection_gesture else {
+            return;
+        };
+        if let Some(pos) = resp.interact_pointer_pos() {
+            let pos = meta.screen_to_canvas_pos(self.local_pos(resp, pos));
+            match gesture {
+                SelectionGesture::Box(_, end) => *end = pos,
+                SelectionGesture::Lasso(points) => {
+                    // Skip points closer than a pixel to keep the path short.
+                    if points
+                        .last()
+                        .is_none_or(|last| last.distance(pos) * meta.zoom >= 1.0)
+                    {
+                        points.push(pos);
+                    }
+                }
+            }
+        }
+
+        if resp.dragged() {
+            return;
+        }
+
+        let Some(gesture) = view.instance.selection_gesture.take() else {
+            return;
+        };
+        let mode = SelectionMode::from_modifiers(modifiers);
+        match gesture {
+            SelectionGesture::Box(start, end) => {
+                let rect = Rect::from_two_pos(start, end);
+                let nodes: Vec<_> = self
+                    .g
+                    .nodes_in_canvas_rect(rect)
+                    .into_iter()
+                    .filter(|&idx| self.node_intersects(idx, rect))
+                    .collect();
+                let edges = (eff.edge_selection).then(|| {
+                    self.g
+                        .edges_in_canvas_rect(rect)
+                        .into_iter()
+                        .filter(|&idx| self.edge_intersects(idx, rect))
+                        .collect::<Vec<_>>()
+                });
+                self.apply_selection(&nodes, edges.as_deref(), mode);
+            }
+            SelectionGesture::Lasso(points) => {
+                let nodes: Vec<_> = self
+                    .g
+                    .nodes_in_canvas_rect(Rect::from_points(&points))
+                    .into_iter()
+                    .filter(|&idx| polygon_contains(&points, self.node_center(idx)))
+                    .collect();
+                self.apply_selection(&nodes, None, mode);
+            }
+        }
+    }
+
+    /// Center of the node bounds, taken from its boundary points along both axes.
+    fn node_center(&self, idx: NodeIndex<Ix>) -> Pos2 {
+        let display = self.g.node(idx).unwrap().display();
+        Rect::from_points(
+            &[Vec2::X, -Vec2::X, Vec2::Y, -Vec2::Y].map(|dir| display.closest_boundary_point(dir)),
+        )
+        .center()
+    }
+
+    /// Whether the node shape intersects `rect`, both in canvas coordinates. Tests the point
+    /// of `rect` closest to the node center.
+    fn node_intersects(&self, idx: NodeIndex<Ix>, rect: Rect) -> bool {
+        let center = self.node_center(idx);
+        rect.contains(center)
+            || self
+                .g
+                .node(idx)
+                .unwrap()
+                .display()
+                .is_inside(rect.clamp(center))
+    }
+
+    /// Whether the edge intersects `rect`, both in canvas coordinates. Curved edges are
+    /// approximated by the line between their endpoints.
+    fn edge_intersects(&self, idx: EdgeIndex<Ix>, rect: Rect) -> bool {
+        let (start, end) = self.g.edge_endpoints(idx).unwrap();
+        self.node_intersects(start, rect)
+            || self.node_intersects(end, rect)
+            || segment_intersects_rect(
+                self.g.node(start).unwrap().location(),
+                self.g.node(end).unwrap().location(),
+                rect,
+            )
+    }
+
+    /// Combines `nodes` and, if given, `edges` with the current selection, emitting select
+    /// and deselect events only for elements whose state changes. The selected lists of the
+    /// graph are updated in place, so the cost follows the selection size, not the graph.
+    fn apply_selection(
+        &mut self,
+        nodes: &[NodeIndex<Ix>],
+        edges: Option<&[EdgeIndex<Ix>]>,
+        mode: SelectionMode,
+    ) {
+        let hits: HashSet<NodeIndex<Ix>> = nodes.iter().copied().collect();
+        let mut selected_nodes = Vec::new();
+        for idx in self.g.selected_nodes().to_vec() {
+            match mode {
+                SelectionMode::Replace if !hits.contains(&idx) => self.deselect_node(idx),
+                SelectionMode::Subtract if hits.contains(&idx) => self.deselect_node(idx),
+                _ => selected_nodes.push(idx),
+            }
+        }
+        if mode != SelectionMode::Subtract {
+            for &idx in nodes {
+                if !self.g.node(idx).unwrap().selected() {
+                    self.select_node(idx);
+                    selected_nodes.push(idx);
+                }
+            }
+        }
+        selected_nodes.sort_unstable();
+        self.g.set_selected_nodes(selected_nodes);
+
+        let Some(edges) = edges else {
+            return;
+        };
+        let hits: HashSet<EdgeIndex<Ix>> = edges.iter().copied().collect();
+        let mut selected_edges = Vec::new();
+        for idx in self.g.selected_edges().to_vec() {
+            match mode {
+                SelectionMode::Replace if !hits.contains(&idx) => self.deselect_edge(idx),
+                SelectionMode::Subtract if hits.contains(&idx) => self.deselect_edge(idx),
+                _ => selected_edges.push(idx),
+            }
+        }
+        if mode != SelectionMode::Subtract {
+            for &idx in edges {
+                if !self.g.edge(idx).unwrap().selected() {
+                    self.select_edge(idx);
+                    selected_edges.push(idx);
+                }
+            }
+        }
+        selected_edges.sort_unstable();
+        self.g.set_selected_edges(selected_edges);
+    }
+
     /// Convert a screen-space position to widget-local position
     fn local_pos(&self, resp: &Response, p: Pos2) -> Pos2 {
         (p - resp.rect.left_top()).to_pos2()
@@ -1138,8 +1361,12 @@ where
     }
 
     fn set_drag_end(&mut self, idx: NodeIndex<Ix>) {
+        let pin = self.settings_interaction.node_pin_on_drag_enabled;
         let n = self.g.node_mut(idx).unwrap();
         n.set_dragged(false);
+        if pin {
+            n.set_pinned(true);
+        }
 
         #[cfg(feature = "events")]
         self.publish_event(Event::NodeDragEnd(PayloadNodeDragEnd { id: idx.index() }));
@@ -1333,3 +1560,149 @@ pub fn get_layout_state<S: LayoutState>(ui: &egui::Ui, id: Option<String>) -> S
 pub fn set_layout_state<S: LayoutState>(ui: &mut egui::Ui, state: S, id: Option<String>) {
     state.save(ui, id);
 }
+
+/// Whether the segment from `a` to `b` intersects `rect`, by clipping it against the
+/// rect's slabs.
+fn segment_intersects_rect(a: Pos2, b: Pos2, rect: Rect) -> bool {
+    let d = b - a;
+    let (mut t0, mut t1) = (0.0f32, 1.0f32);
+    for (delta, start, min, max) in [
+        (d.x, a.x, rect.min.x, rect.max.x),
+        (d.y, a.y, rect.min.y, rect.max.y),
+    ] {
+        if delta == 0.0 {
+            if start < min || start > max {
+                return false;
+            }
+            continue;
+        }
+        let (mut near, mut far) = ((min - start) / delta, (max - start) / delta);
+        if near > far {
+            std::mem::swap(&mut near, &mut far);
+        }
+        t0 = t0.max(near);
+        t1 = t1.min(far);
+        if t0 > t1 {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether `p` lies inside the polygon closed from `points`, by the even-odd rule.
+fn polygon_contains(points: &[Pos2], p: Pos2) -> bool {
+    let mut inside = false;
+    let mut prev = match points.last() {
+        Some(&last) => last,
+        None => return false,
+    };
+    for &cur in points {
+        if (cur.y > p.y) != (prev.y > p.y)
+            && p.x < (prev.x - cur.x) * (p.y - cur.y) / (prev.y - cur.y) + cur.x
+        {
+            inside = !inside;
+        }
+        prev = cur;
+    }
+    inside
+}
+
+/// Paints the rectangle or path of an ongoing box or lasso selection.
+fn draw_selection_gesture(
+    ui: &Ui,
+    painter: &Painter,
+    meta: &MetadataFrame,
+    instance: &MetadataInstance,
+) {
+    let selection = ui.visuals().selection;
+    match &instance.selection_gesture {
+        Some(SelectionGesture::Box(start, end)) => {
+            let rect = Rect::from_two_pos(
+                meta.canvas_to_screen_pos(*start),
+                meta.canvas_to_screen_pos(*end),
+            );
+            painter.rect(
+                rect,
+                0.0,
+                selection.bg_fill.gamma_multiply(0.25),
+                selection.stroke,
+                StrokeKind::Inside,
+            );
+        }
+        Some(SelectionGesture::Lasso(points)) => {
+            let points = points
+                .iter()
+                .map(|p| meta.canvas_to_screen_pos(*p))
+                .collect();
+            painter.add(Shape::closed_line(points, selection.stroke));
+        }
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use petgraph::stable_graph::StableGraph;
+
+    #[test]
+    fn box_selection_combines_with_current_selection() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let (a, b, c) = (sg.add_node(()), sg.add_node(()), sg.add_node(()));
+        let e = sg.add_edge(a, b, ());
+        let mut g: Graph = Graph::from(&sg);
+        g.node_mut(a).unwrap().set_selected(true);
+        g.set_selected_nodes(vec![a]);
+        let selected = |g: &Graph| (g.selected_nodes().to_vec(), g.selected_edges().to_vec());
+
+        let mut view = DefaultGraphView::new(&mut g);
+        view.apply_selection(&[b, c], Some(&[e]), SelectionMode::Replace);
+        assert_eq!(selected(view.g), (vec![b, c], vec![e]));
+        view.apply_selection(&[c], None, SelectionMode::Subtract);
+        assert_eq!(selected(view.g), (vec![b], vec![e]));
+        view.apply_selection(&[a], None, SelectionMode::Add);
+        assert_eq!(selected(view.g), (vec![a, b], vec![e]));
+
+        let rect = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(10.0, 10.0));
+        assert!(segment_intersects_rect(
+            Pos2::new(-5.0, 5.0),
+            Pos2::new(15.0, 5.0),
+            rect
+        ));
+        assert!(segment_intersects_rect(
+            Pos2::new(-5.0, 0.0),
+            Pos2::new(5.0, 20.0),
+            rect
+        ));
+        assert!(!segment_intersects_rect(
+            Pos2::new(-5.0, 5.0),
+            Pos2::new(5.0, 20.0),
+            rect
+        ));
+        assert!(!segment_intersects_rect(
+            Pos2::new(12.0, -5.0),
+            Pos2::new(12.0, 15.0),
+            rect
+        ));
+    }
+
+    #[test]
+    fn lasso_contains_points_of_concave_path() {
+        // U shape opening upwards: the notch between the arms is outside.
+        let points = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(3.0, 0.0),
+            Pos2::new(3.0, 10.0),
+            Pos2::new(7.0, 10.0),
+            Pos2::new(7.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 20.0),
+            Pos2::new(0.0, 20.0),
+        ];
+        assert!(polygon_contains(&points, Pos2::new(1.0, 5.0)));
+        assert!(polygon_contains(&points, Pos2::new(5.0, 15.0)));
+        assert!(!polygon_contains(&points, Pos2::new(5.0, 5.0)));
+        assert!(!polygon_contains(&points, Pos2::new(15.0, 5.0)));
+        assert!(!polygon_contains(&[], Pos2::ZERO));
+    }
+}
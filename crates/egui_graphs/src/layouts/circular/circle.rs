@@ -0,0 +1,223 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use egui::{Pos2, Vec2};
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+/// Order in which nodes are placed around the circle.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum CircleOrder {
+    /// Node index order, i.e. the order nodes were added in.
+    #[default]
+    Insertion,
+    /// Highest degree first.
+    Degree,
+    /// Ascending by the keys set with [`CircleState::with_node_keys`].
+    /// Nodes without a key follow in insertion order.
+    Key,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircleState {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Circle radius. When unset the radius grows with the node count so that
+    /// neighbouring nodes are `spacing` apart.
+    pub radius: Option<f32>,
+    /// Arc length between neighbouring nodes used when `radius` is unset.
+    pub spacing: f32,
+    /// Angle of the first node, in radians clockwise from the positive X axis.
+    pub start_angle: f32,
+    pub order: CircleOrder,
+    /// Ordering keys keyed by node index, used by [`CircleOrder::Key`].
+    /// Fill via [`CircleState::with_node_keys`].
+    pub keys: HashMap<usize, f32>,
+}
+
+impl Default for CircleState {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            radius: None,
+            spacing: 40.0,
+            start_angle: 0.0,
+            order: CircleOrder::Insertion,
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl LayoutState for CircleState {
+    fn is_done(&self) -> bool {
+        self.triggered
+    }
+}
+
+impl CircleState {
+    /// Orders nodes by `f` over every node of `g` and switches to [`CircleOrder::Key`].
+    ///
+    /// Keys are stored by node index, call again after changing the graph nodes.
+    pub fn with_node_keys<N, E, Ty, Ix, Dn, De>(
+        mut self,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        f: impl Fn(NodeIndex<Ix>, &N) -> f32,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.keys = node_keys(g, f);
+        self.order = CircleOrder::Key;
+        self
+    }
+}
+
+/// Places all nodes evenly on a single circle around the origin.
+#[derive(Debug, Default)]
+pub struct Circle {
+    state: CircleState,
+}
+
+impl Layout<CircleState> for Circle {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        self.place(g);
+
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> CircleState {
+        self.state.clone()
+    }
+
+    fn from_state(state: CircleState) -> impl Layout<CircleState> {
+        Circle { state }
+    }
+}
+
+impl Circle {
+    fn place<N, E, Ty, Ix, Dn, De>(&self, g: &mut Graph<N, E, Ty, Ix, Dn, De>)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut nodes: Vec<NodeIndex<Ix>> = g.g().node_indices().collect();
+        match self.state.order {
+            CircleOrder::Insertion => {}
+            CircleOrder::Degree => {
+                nodes.sort_by_key(|&n| std::cmp::Reverse(g.g().neighbors_undirected(n).count()));
+            }
+            CircleOrder::Key => {
+                let key = |n: NodeIndex<Ix>| self.state.keys.get(&n.index()).copied();
+                nodes.sort_by(|&a, &b| match (key(a), key(b)) {
+                    (Some(a), Some(b)) => a.total_cmp(&b),
+                    (a, b) => b.is_some().cmp(&a.is_some()),
+                });
+            }
+        }
+
+        let radius = self
+            .state
+            .radius
+            .unwrap_or(nodes.len() as f32 * self.state.spacing / TAU);
+        place_on_ring(g, &nodes, radius, self.state.start_angle);
+    }
+}
+
+pub(super) fn node_keys<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    f: impl Fn(NodeIndex<Ix>, &N) -> f32,
+) -> HashMap<usize, f32>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    g.nodes_iter()
+        .filter_map(|(idx, n)| {
+            let k = f(idx, n.payload());
+            k.is_finite().then_some((idx.index(), k))
+        })
+        .collect()
+}
+
+/// Spreads `nodes` evenly on a circle of `radius` around the origin. A single node on a
+/// ring is placed at the ring's start angle. Pinned nodes keep their slot but are not moved.
+pub(super) fn place_on_ring<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    nodes: &[NodeIndex<Ix>],
+    radius: f32,
+    start_angle: f32,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let step = TAU / nodes.len().max(1) as f32;
+    for (i, &n) in nodes.iter().enumerate() {
+        let node = &mut g.g_mut()[n];
+        if !node.pinned() {
+            node.set_location(Pos2::ZERO + radius * Vec2::angled(start_angle + step * i as f32));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<u32, (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    #[test]
+    fn key_order_follows_closure() {
+        let mut sg: StableGraph<u32, ()> = StableGraph::new();
+        let ns: Vec<_> = [3, 1, 2, 0].into_iter().map(|k| sg.add_node(k)).collect();
+        let mut g: G = Graph::from(&sg);
+
+        let state = CircleState {
+            radius: Some(100.0),
+            ..CircleState::default()
+        }
+        .with_node_keys(&g, |_, &k| k as f32);
+        Circle { state }.place(&mut g);
+
+        // Key 0 first at the start angle, then clockwise by key.
+        let loc = |i: usize| g.g()[ns[i]].location();
+        assert!((loc(3) - Pos2::new(100.0, 0.0)).length() < 1e-3);
+        assert!((loc(1) - Pos2::new(0.0, 100.0)).length() < 1e-3);
+        assert!((loc(2) - Pos2::new(-100.0, 0.0)).length() < 1e-3);
+        assert!((loc(0) - Pos2::new(0.0, -100.0)).length() < 1e-3);
+    }
+}
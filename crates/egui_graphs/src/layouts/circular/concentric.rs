@@ -0,0 +1,220 @@
+use std::{collections::HashMap, f32::consts::TAU};
+
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+use super::circle::{node_keys, place_on_ring};
+
+/// Metric deciding the ring of every node. Higher values are placed closer to the center.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ConcentricMetric {
+    #[default]
+    Degree,
+    /// Values set with [`ConcentricState::with_node_metric`]. Nodes without a value count as `0`.
+    Key,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcentricState {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Minimal distance between consecutive rings.
+    pub ring_dist: f32,
+    /// Minimal arc length between neighbouring nodes on a ring. Crowded rings grow beyond
+    /// `ring_dist` to keep it.
+    pub spacing: f32,
+    /// Range of metric values sharing one ring, counted down from the highest value.
+    /// When unset every distinct value gets its own ring.
+    pub level_width: Option<f32>,
+    /// Angle of the first node on every ring, in radians clockwise from the positive X axis.
+    pub start_angle: f32,
+    pub metric: ConcentricMetric,
+    /// Metric values keyed by node index, used by [`ConcentricMetric::Key`].
+    /// Fill via [`ConcentricState::with_node_metric`].
+    pub values: HashMap<usize, f32>,
+}
+
+impl Default for ConcentricState {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            ring_dist: 80.0,
+            spacing: 40.0,
+            level_width: None,
+            start_angle: 0.0,
+            metric: ConcentricMetric::Degree,
+            values: HashMap::new(),
+        }
+    }
+}
+
+impl LayoutState for ConcentricState {
+    fn is_done(&self) -> bool {
+        self.triggered
+    }
+}
+
+impl ConcentricState {
+    /// Uses `f` over every node of `g` as its metric and switches to [`ConcentricMetric::Key`].
+    /// Any score works, e.g. a precomputed centrality looked up by node index.
+    ///
+    /// Values are stored by node index, call again after changing the graph nodes.
+    pub fn with_node_metric<N, E, Ty, Ix, Dn, De>(
+        mut self,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        f: impl Fn(NodeIndex<Ix>, &N) -> f32,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.values = node_keys(g, f);
+        self.metric = ConcentricMetric::Key;
+        self
+    }
+}
+
+/// Places nodes on concentric rings around the origin, grouping them by a metric with the
+/// highest values in the middle.
+#[derive(Debug, Default)]
+pub struct Concentric {
+    state: ConcentricState,
+}
+
+impl Layout<ConcentricState> for Concentric {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        self.place(g);
+
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> ConcentricState {
+        self.state.clone()
+    }
+
+    fn from_state(state: ConcentricState) -> impl Layout<ConcentricState> {
+        Concentric { state }
+    }
+}
+
+impl Concentric {
+    fn place<N, E, Ty, Ix, Dn, De>(&self, g: &mut Graph<N, E, Ty, Ix, Dn, De>)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let state = &self.state;
+        let mut nodes: Vec<(f32, NodeIndex<Ix>)> = g
+            .g()
+            .node_indices()
+            .map(|n| {
+                let value = match state.metric {
+                    ConcentricMetric::Degree => g.g().neighbors_undirected(n).count() as f32,
+                    ConcentricMetric::Key => state.values.get(&n.index()).copied().unwrap_or(0.0),
+                };
+                (value, n)
+            })
+            .collect();
+        nodes.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let Some(&(top, _)) = nodes.first() else {
+            return;
+        };
+
+        // Split the descending sequence into rings.
+        let mut rings: Vec<Vec<NodeIndex<Ix>>> = Vec::new();
+        let mut ring_top = f32::NAN;
+        for (value, n) in nodes {
+            let new_ring = match state.level_width {
+                Some(w) if w > 0.0 => ((top - value) / w).floor() > ((top - ring_top) / w).floor(),
+                _ => value != ring_top,
+            };
+            if rings.is_empty() || new_ring {
+                rings.push(Vec::new());
+                ring_top = value;
+            }
+            rings.last_mut().unwrap().push(n);
+        }
+
+        let mut radius: f32 = 0.0;
+        for (i, ring) in rings.iter().enumerate() {
+            let fit = ring.len() as f32 * state.spacing / TAU;
+            radius = if i == 0 && ring.len() == 1 {
+                0.0
+            } else if i == 0 {
+                fit
+            } else {
+                fit.max(radius + state.ring_dist)
+            };
+            place_on_ring(g, ring, radius, state.start_angle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Undirected, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    fn radius(g: &G, n: NodeIndex) -> f32 {
+        g.g()[n].location().to_vec2().length()
+    }
+
+    #[test]
+    fn hub_in_center_leaves_outside() {
+        // Star with a hub and five leaves plus one extra edge between two leaves.
+        let mut sg: StableGraph<(), (), petgraph::Undirected> = StableGraph::default();
+        let hub = sg.add_node(());
+        let leaves: Vec<_> = (0..5).map(|_| sg.add_node(())).collect();
+        for &l in &leaves {
+            sg.add_edge(hub, l, ());
+        }
+        sg.add_edge(leaves[0], leaves[1], ());
+        let mut g: G = Graph::from(&sg);
+
+        Concentric {
+            state: ConcentricState::default(),
+        }
+        .place(&mut g);
+
+        assert_eq!(g.g()[hub].location(), Pos2::ZERO);
+        assert!((radius(&g, leaves[0]) - radius(&g, leaves[1])).abs() < 1e-3);
+        assert!(radius(&g, leaves[2]) >= radius(&g, leaves[0]) + 80.0 - 1e-3);
+
+        // With a wide enough level all leaves share one ring.
+        let state = ConcentricState {
+            level_width: Some(2.5),
+            ..ConcentricState::default()
+        };
+        Concentric { state }.place(&mut g);
+        assert!((radius(&g, leaves[0]) - radius(&g, leaves[4])).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,5 @@
+mod circle;
+mod concentric;
+
+pub use circle::{Circle, CircleOrder, CircleState};
+pub use concentric::{Concentric, ConcentricMetric, ConcentricState};
@@ -0,0 +1,191 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
+use petgraph::{csr::IndexType, EdgeType};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::algorithm::ForceAlgorithm;
+#[cfg(not(target_arch = "wasm32"))]
+use super::worker::{Job, Worker};
+use crate::{
+    layouts::{AnimatedState, Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+/// State of [`ForceDirectedBackground`]: the wrapped algorithm state plus the worker handle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "S: Serialize", deserialize = "S: DeserializeOwned"))]
+pub struct ForceDirectedBackgroundState<S> {
+    /// State of the force algorithm. The worker keeps stepping its own copy, parameters
+    /// changed here are picked up once [`Self::generation`] changes, see [`Self::set_inner`].
+    pub inner: S,
+    /// Bumped whenever `inner` holds new parameters for the worker.
+    #[serde(default)]
+    pub generation: u64,
+    /// Step the algorithm on a worker thread. When off, and always on wasm, steps run
+    /// synchronously inside `GraphView::ui` just like with [`super::ForceDirected`].
+    pub background: bool,
+    /// Duration of the last step measured on the worker thread, in milliseconds.
+    #[serde(skip)]
+    pub last_step_time_ms: Option<f32>,
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    worker: Option<Arc<Worker<S>>>,
+}
+
+impl<S: Default> Default for ForceDirectedBackgroundState<S> {
+    fn default() -> Self {
+        Self {
+            inner: S::default(),
+            generation: 0,
+            background: true,
+            last_step_time_ms: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            worker: None,
+        }
+    }
+}
+
+impl<S> ForceDirectedBackgroundState<S> {
+    /// Replaces the algorithm state and bumps [`Self::generation`] so the worker restarts
+    /// from it instead of its own copy.
+    pub fn set_inner(&mut self, inner: S) {
+        self.inner = inner;
+        self.generation = self.generation.wrapping_add(1);
+    }
+}
+
+impl<S: LayoutState + AnimatedState + Clone> LayoutState for ForceDirectedBackgroundState<S> {
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        Some(self)
+    }
+}
+
+impl<S: AnimatedState> AnimatedState for ForceDirectedBackgroundState<S> {
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+    fn set_running(&mut self, v: bool) {
+        self.inner.set_running(v);
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        self.inner.last_avg_displacement()
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.inner.set_last_avg_displacement(v);
+    }
+    fn step_count(&self) -> u64 {
+        self.inner.step_count()
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.inner.set_step_count(v);
+    }
+}
+
+/// Runs a [`ForceAlgorithm`] on a worker thread so big graphs do not stall the UI.
+///
+/// Every frame positions and edges are copied for the worker and the positions of the last
+/// finished step are written back, so the layout lags one step behind the graph. Pinned and
+/// dragged nodes are never overwritten. If a step takes longer than a frame the simulation
+/// simply advances slower while the UI keeps its frame rate.
+///
+/// On wasm, or with [`ForceDirectedBackgroundState::background`] off, it behaves exactly
+/// like [`super::ForceDirected`]. The `GraphView::fast_forward*` helpers only pick up
+/// steps the worker has finished, switch `background` off to fast forward synchronously.
+#[derive(Debug, Default)]
+pub struct ForceDirectedBackground<A: ForceAlgorithm> {
+    state: ForceDirectedBackgroundState<A::State>,
+}
+
+impl<A> Layout<ForceDirectedBackgroundState<A::State>> for ForceDirectedBackground<A>
+where
+    A: ForceAlgorithm,
+    A::State: AnimatedState,
+{
+    fn from_state(
+        state: ForceDirectedBackgroundState<A::State>,
+    ) -> impl Layout<ForceDirectedBackgroundState<A::State>> {
+        Self { state }
+    }
+
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, ui: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if g.node_count() == 0 {
+            return;
+        }
+        let view = ui.ctx().content_rect();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if self.state.background {
+                self.next_background(g, view);
+                return;
+            }
+            self.state.worker = None;
+        }
+
+        let mut alg = A::from_state(self.state.inner.clone());
+        alg.step(g, view);
+        self.state.inner = alg.state();
+    }
+
+    fn state(&self) -> ForceDirectedBackgroundState<A::State> {
+        self.state.clone()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<A> ForceDirectedBackground<A>
+where
+    A: ForceAlgorithm,
+    A::State: AnimatedState,
+{
+    fn next_background<N, E, Ty, Ix, Dn, De>(
+        &mut self,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+        view: egui::Rect,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let worker = self
+            .state
+            .worker
+            .get_or_insert_with(|| Arc::new(Worker::spawn::<A>()));
+
+        if let Some(out) = worker.take_output() {
+            for (idx, pos) in out.positions {
+                if let Some(node) = g.node_mut(petgraph::stable_graph::NodeIndex::new(idx)) {
+                    if !node.pinned() && !node.dragged() {
+                        node.set_location(pos);
+                    }
+                }
+            }
+            self.state.inner.set_step_count(out.step_count);
+            self.state
+                .inner
+                .set_last_avg_displacement(out.last_avg_displacement);
+            self.state.last_step_time_ms = Some(out.step_time_ms);
+        }
+
+        if self.state.inner.is_running() && !worker.is_busy() {
+            worker.submit(Job::snapshot(
+                g,
+                self.state.inner.clone(),
+                self.state.generation,
+                view,
+            ));
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use egui::{Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::layouts::force_directed::parallel::{for_each_node, positions};
+use crate::{node_size, DisplayEdge, DisplayNode, Graph, Node};
+use petgraph::EdgeType;
+
+/// How [`BoundingBox`] keeps nodes inside the rectangle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BoundingBoxMode {
+    /// Nodes outside the rectangle are pulled back by a spring proportional to how far out they are.
+    #[default]
+    Spring,
+    /// Nodes are moved back onto the rectangle border after every step. Pinned and dragged
+    /// nodes are left where they are.
+    Clamp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundingBoxParams {
+    pub mode: BoundingBoxMode,
+    /// Spring strength used in [`BoundingBoxMode::Spring`].
+    pub c: f32,
+    /// Gap kept between node borders and the rectangle.
+    pub padding: f32,
+    /// Fixed canvas rectangle. When `None` the layout's viewport area is used.
+    pub rect: Option<Rect>,
+}
+impl Default for BoundingBoxParams {
+    fn default() -> Self {
+        Self {
+            mode: BoundingBoxMode::default(),
+            c: 0.5,
+            padding: 10.0,
+            rect: None,
+        }
+    }
+}
+
+impl BoundingBoxParams {
+    fn bounds(&self, area: Rect) -> Rect {
+        self.rect.unwrap_or(area).shrink(self.padding)
+    }
+}
+
+/// Keeps nodes within a rectangle: either a fixed canvas rect from the params or the
+/// viewport area the layout runs in. Node extents are taken into account, so the whole
+/// shape stays inside.
+#[derive(Debug, Default)]
+pub struct BoundingBox;
+
+impl ExtraForce for BoundingBox {
+    type Params = BoundingBoxParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        area: Rect,
+        _k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.mode != BoundingBoxMode::Spring || params.c == 0.0 {
+            return;
+        }
+        let bounds = params.bounds(area);
+        let positions = positions(g, indices);
+        let extents: Vec<Vec2> = indices
+            .iter()
+            .map(|&idx| extent(g.g().node_weight(idx).unwrap()))
+            .collect();
+        for_each_node(&mut disp[..indices.len()], |i, d| {
+            let target = clamp_into(bounds, positions[i], extents[i]);
+            *d += (target - positions[i]) * params.c;
+        });
+    }
+
+    fn constrain<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        area: Rect,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.mode != BoundingBoxMode::Clamp {
+            return;
+        }
+        let bounds = params.bounds(area);
+        for &idx in indices {
+            let n = g.g_mut().node_weight_mut(idx).unwrap();
+            if n.pinned() || n.dragged() {
+                continue;
+            }
+            let loc = n.location();
+            let clamped = clamp_into(bounds, loc, extent(n));
+            if clamped != loc {
+                n.set_location(clamped);
+            }
+        }
+    }
+}
+
+fn extent<N, E, Ty, Ix, D>(n: &Node<N, E, Ty, Ix, D>) -> Vec2
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: petgraph::csr::IndexType,
+    D: DisplayNode<N, E, Ty, Ix>,
+{
+    Vec2::new(node_size(n, Vec2::X), node_size(n, Vec2::Y))
+}
+
+/// Closest point to `p` at which a node with half-size `extent` fits inside `bounds`.
+/// On an axis where the node is wider than the bounds it is centered instead.
+fn clamp_into(bounds: Rect, p: Pos2, extent: Vec2) -> Pos2 {
+    let axis = |p: f32, min: f32, max: f32, half: f32| {
+        let (lo, hi) = (min + half, max - half);
+        if lo > hi {
+            (min + max) / 2.0
+        } else {
+            p.clamp(lo, hi)
+        }
+    };
+    Pos2::new(
+        axis(p.x, bounds.min.x, bounds.max.x, extent.x),
+        axis(p.y, bounds.min.y, bounds.max.y, extent.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    #[test]
+    fn spring_pulls_back_and_clamp_respects_pinned() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        for _ in 0..3 {
+            sg.add_node(());
+        }
+        let mut g: G = Graph::from(&sg);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        for (&idx, x) in indices.iter().zip([50.0, 300.0, -200.0]) {
+            g.node_mut(idx).unwrap().set_location(Pos2::new(x, 50.0));
+        }
+        g.node_mut(indices[2]).unwrap().set_pinned(true);
+        let area = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0));
+
+        let mut disp = vec![Vec2::ZERO; 3];
+        let spring = BoundingBoxParams::default();
+        BoundingBox::apply(&spring, &g, &indices, &mut disp, area, 1.0);
+        assert_eq!(disp[0], Vec2::ZERO);
+        assert!(disp[1].x < 0.0 && disp[1].y == 0.0);
+
+        let clamp = BoundingBoxParams {
+            mode: BoundingBoxMode::Clamp,
+            ..Default::default()
+        };
+        BoundingBox::constrain(&clamp, &mut g, &indices, area);
+        // Default radius 5 plus padding 10 keeps centers within [15, 85].
+        assert_eq!(
+            g.node(indices[0]).unwrap().location(),
+            Pos2::new(50.0, 50.0)
+        );
+        assert_eq!(
+            g.node(indices[1]).unwrap().location(),
+            Pos2::new(85.0, 50.0)
+        );
+        assert_eq!(
+            g.node(indices[2]).unwrap().location(),
+            Pos2::new(-200.0, 50.0)
+        );
+    }
+}
@@ -2,6 +2,7 @@ use egui::{Rect, Vec2};
 use serde::{Deserialize, Serialize};
 
 use super::core::ExtraForce;
+use crate::layouts::force_directed::parallel::{for_each_node, positions};
 use crate::{DisplayEdge, DisplayNode, Graph};
 use petgraph::EdgeType;
 
@@ -40,10 +41,9 @@ impl ExtraForce for CenterGravity {
             return;
         }
         let center = area.center();
-        for (vec_pos, &idx) in indices.iter().enumerate() {
-            let pos = g.g().node_weight(idx).unwrap().location();
-            let delta = center - pos;
-            disp[vec_pos] += delta * params.c;
-        }
+        let positions = positions(g, indices);
+        for_each_node(&mut disp[..indices.len()], |i, d| {
+            *d += (center - positions[i]) * params.c;
+        });
     }
 }
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use egui::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::layouts::force_directed::parallel::{for_each_node, positions};
+use crate::{node_size, DisplayEdge, DisplayNode, Graph};
+use petgraph::EdgeType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollisionParams {
+    /// Spring strength pushing overlapping nodes apart, relative to the ideal edge length `k`.
+    pub c: f32,
+    /// Extra gap kept between node borders.
+    pub padding: f32,
+}
+impl Default for CollisionParams {
+    fn default() -> Self {
+        Self {
+            c: 1.0,
+            padding: 4.0,
+        }
+    }
+}
+
+/// Pushes apart nodes whose shapes (plus `padding`) overlap.
+///
+/// Node extents come from [`DisplayNode::closest_boundary_point`] along both axes, so
+/// non-circular shapes such as wide labelled nodes are treated as ellipses. Only nearby
+/// pairs are checked, using a uniform grid sized after the biggest node.
+#[derive(Debug, Default)]
+pub struct Collision;
+
+impl ExtraForce for Collision {
+    type Params = CollisionParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        _area: Rect,
+        k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.c == 0.0 || indices.len() < 2 {
+            return;
+        }
+        let positions = positions(g, indices);
+        let extents: Vec<Vec2> = indices
+            .iter()
+            .map(|&idx| {
+                let n = g.g().node_weight(idx).unwrap();
+                Vec2::new(node_size(n, Vec2::X), node_size(n, Vec2::Y))
+            })
+            .collect();
+
+        let cell = 2.0 * extents.iter().map(|e| e.max_elem()).fold(0.0, f32::max) + params.padding;
+        if cell <= 0.0 || !cell.is_finite() {
+            return;
+        }
+        let key = |i: usize| {
+            let p = positions[i];
+            ((p.x / cell).floor() as i64, (p.y / cell).floor() as i64)
+        };
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for i in 0..positions.len() {
+            grid.entry(key(i)).or_default().push(i);
+        }
+
+        for_each_node(&mut disp[..indices.len()], |i, d| {
+            let (cx, cy) = key(i);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let Some(cell_nodes) = grid.get(&(cx + dx, cy + dy)) else {
+                        continue;
+                    };
+                    for &j in cell_nodes {
+                        if j == i {
+                            continue;
+                        }
+                        let mut delta = positions[i] - positions[j];
+                        let mut dist = delta.length();
+                        if dist < 1e-6 {
+                            // Coincident nodes: split them along a fixed, index-based direction.
+                            delta = Vec2::angled(i.min(j) as f32 * 2.399);
+                            if i > j {
+                                delta = -delta;
+                            }
+                            dist = 0.0;
+                        }
+                        let dir = delta.normalized();
+                        let reach = ellipse_radius(extents[i], dir)
+                            + ellipse_radius(extents[j], dir)
+                            + params.padding;
+                        let overlap = reach - dist;
+                        if overlap > 0.0 {
+                            *d += dir * (params.c * k * overlap);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Distance from the center to the border of an ellipse with half-axes `extent` along `dir`.
+fn ellipse_radius(extent: Vec2, dir: Vec2) -> f32 {
+    let (a, b) = (extent.x, extent.y);
+    let denom = ((b * dir.x).powi(2) + (a * dir.y).powi(2)).sqrt();
+    if denom <= f32::EPSILON {
+        a.max(b)
+    } else {
+        a * b / denom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    #[test]
+    fn pushes_apart_only_overlapping_nodes() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        for _ in 0..3 {
+            sg.add_node(());
+        }
+        let mut g: G = Graph::from(&sg);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        // Default radius is 5: the first two overlap, the third is far away.
+        for (&idx, x) in indices.iter().zip([0.0, 6.0, 100.0]) {
+            g.node_mut(idx).unwrap().set_location(Pos2::new(x, 0.0));
+        }
+
+        let mut disp = vec![Vec2::ZERO; 3];
+        let area = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0));
+        Collision::apply(
+            &CollisionParams::default(),
+            &g,
+            &indices,
+            &mut disp,
+            area,
+            1.0,
+        );
+
+        assert!(disp[0].x < 0.0 && disp[1].x > 0.0);
+        assert_eq!(disp[0], -disp[1]);
+        assert_eq!(disp[2], Vec2::ZERO);
+    }
+}
@@ -24,6 +24,23 @@ pub trait ExtraForce: std::fmt::Debug + Default + Send + Sync + 'static {
         Ix: petgraph::csr::IndexType,
         Dn: DisplayNode<N, E, Ty, Ix>,
         De: DisplayEdge<N, E, Ty, Ix, Dn>;
+
+    /// Optional hook run after the step moved the nodes, for hard constraints on positions.
+    /// Does nothing by default.
+    fn constrain<N, E, Ty, Ix, Dn, De>(
+        _params: &Self::Params,
+        _g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+        _indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        _area: Rect,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+    }
 }
 
 /// A configured instance of an extra force (on/off + parameters).
@@ -91,6 +108,20 @@ pub trait ExtrasTuple:
         Ix: petgraph::csr::IndexType,
         Dn: DisplayNode<N, EE, Ty, Ix>,
         De: DisplayEdge<N, EE, Ty, Ix, Dn>;
+
+    /// Run the `constrain` hook of every enabled extra, after positions were updated.
+    fn constrain_all<N, EE, Ty, Ix, Dn, De>(
+        &self,
+        g: &mut Graph<N, EE, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        area: Rect,
+    ) where
+        N: Clone,
+        EE: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, EE, Ty, Ix>,
+        De: DisplayEdge<N, EE, Ty, Ix, Dn>;
 }
 
 impl ExtrasTuple for () {
@@ -110,6 +141,21 @@ impl ExtrasTuple for () {
         De: DisplayEdge<N, EE, Ty, Ix, Dn>,
     {
     }
+
+    fn constrain_all<N, EE, Ty, Ix, Dn, De>(
+        &self,
+        _g: &mut Graph<N, EE, Ty, Ix, Dn, De>,
+        _indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        _area: Rect,
+    ) where
+        N: Clone,
+        EE: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, EE, Ty, Ix>,
+        De: DisplayEdge<N, EE, Ty, Ix, Dn>,
+    {
+    }
 }
 
 impl<Head, const B: bool, Tail> ExtrasTuple for (Extra<Head, B>, Tail)
@@ -139,4 +185,24 @@ where
         }
         tail.apply_all(g, indices, disp, area, k);
     }
+
+    fn constrain_all<N, EE, Ty, Ix, Dn, De>(
+        &self,
+        g: &mut Graph<N, EE, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        area: Rect,
+    ) where
+        N: Clone,
+        EE: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, EE, Ty, Ix>,
+        De: DisplayEdge<N, EE, Ty, Ix, Dn>,
+    {
+        let (head, tail) = self;
+        if head.enabled {
+            Head::constrain(&head.params, g, indices, area);
+        }
+        tail.constrain_all(g, indices, area);
+    }
 }
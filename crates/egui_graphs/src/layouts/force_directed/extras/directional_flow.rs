@@ -0,0 +1,143 @@
+use egui::{Rect, Vec2};
+use petgraph::{
+    visit::EdgeRef,
+    Direction::{Incoming, Outgoing},
+    EdgeType,
+};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::layouts::force_directed::parallel::for_each_node;
+use crate::layouts::hierarchical::Orientation;
+use crate::{DisplayEdge, DisplayNode, Graph};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectionalFlowParams {
+    /// Direction edges should point in, from source to target.
+    pub orientation: Orientation,
+    /// Spring strength pushing edge ends apart along the flow axis.
+    pub c: f32,
+    /// Minimum offset of a target past its source along the flow axis, relative to the
+    /// ideal edge length `k`.
+    pub separation: f32,
+}
+impl Default for DirectionalFlowParams {
+    fn default() -> Self {
+        Self {
+            orientation: Orientation::TopDown,
+            c: 0.5,
+            separation: 0.5,
+        }
+    }
+}
+
+/// Makes directed edges flow along one axis, like a DAG mode for force layouts.
+///
+/// Every edge whose target is not at least `separation * k` past its source along the
+/// orientation's flow axis pushes both ends apart along that axis. Undirected graphs are
+/// left untouched.
+#[derive(Debug, Default)]
+pub struct DirectionalFlow;
+
+impl ExtraForce for DirectionalFlow {
+    type Params = DirectionalFlowParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        _area: Rect,
+        k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if !g.is_directed() || params.c == 0.0 || indices.is_empty() {
+            return;
+        }
+        let axis = params.orientation.flow_axis();
+        let min_offset = params.separation * k;
+
+        // Offset along the axis from every edge's source to its target, listed once for
+        // each endpoint with the sign of the push that endpoint receives.
+        let mut offsets = Vec::with_capacity(indices.len() + 1);
+        let mut pushes: Vec<(f32, f32)> = Vec::new();
+        offsets.push(0);
+        for &idx in indices {
+            let here = g.g()[idx].location();
+            for e in g.g().edges_directed(idx, Outgoing) {
+                if e.target() != idx {
+                    let there = g.g()[e.target()].location();
+                    pushes.push(((there - here).dot(axis), -1.0));
+                }
+            }
+            for e in g.g().edges_directed(idx, Incoming) {
+                if e.source() != idx {
+                    let there = g.g()[e.source()].location();
+                    pushes.push(((here - there).dot(axis), 1.0));
+                }
+            }
+            offsets.push(pushes.len());
+        }
+
+        for_each_node(&mut disp[..indices.len()], |i, d| {
+            for &(offset, sign) in &pushes[offsets[i]..offsets[i + 1]] {
+                let violation = min_offset - offset;
+                if violation > 0.0 {
+                    *d += axis * (sign * params.c * violation);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    #[test]
+    fn pushes_backward_edges_along_flow_axis() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let a = sg.add_node(());
+        let b = sg.add_node(());
+        let c = sg.add_node(());
+        sg.add_edge(a, b, ());
+        sg.add_edge(b, c, ());
+        let mut g: Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape> =
+            Graph::from(&sg);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        // a -> b points up (against TopDown), b -> c already points well down.
+        for (&idx, y) in indices.iter().zip([0.0, -10.0, 100.0]) {
+            g.node_mut(idx).unwrap().set_location(Pos2::new(0.0, y));
+        }
+
+        let mut disp = vec![Vec2::ZERO; 3];
+        let area = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0));
+        let params = DirectionalFlowParams::default();
+        DirectionalFlow::apply(&params, &g, &indices, &mut disp, area, 20.0);
+
+        // Violation is 0.5 * 20 + 10 = 20, scaled by c = 0.5.
+        assert_eq!(disp[0], Vec2::new(0.0, -10.0));
+        assert_eq!(disp[1], Vec2::new(0.0, 10.0));
+        assert_eq!(disp[2], Vec2::ZERO);
+
+        let mut sg: StableGraph<(), (), petgraph::Undirected> = StableGraph::default();
+        let a = sg.add_node(());
+        let b = sg.add_node(());
+        sg.add_edge(a, b, ());
+        let g: Graph<(), (), petgraph::Undirected, u32, DefaultNodeShape, DefaultEdgeShape> =
+            Graph::from(&sg);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        let mut disp = vec![Vec2::ZERO; 2];
+        DirectionalFlow::apply(&params, &g, &indices, &mut disp, area, 20.0);
+        assert!(disp.iter().all(|d| *d == Vec2::ZERO));
+    }
+}
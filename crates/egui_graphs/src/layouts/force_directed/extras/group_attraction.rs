@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use egui::{Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+use super::core::ExtraForce;
+use crate::layouts::force_directed::parallel::{for_each_node, positions};
+use crate::{DisplayEdge, DisplayNode, Graph};
+use petgraph::EdgeType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupAttractionParams {
+    /// Strength of the pull towards the group centroid.
+    pub c: f32,
+    /// Strength of the repulsion between group centroids, relative to `k²`. `0.0` disables it.
+    pub c_repulse: f32,
+    /// Group id keyed by node index. Nodes without an entry are not affected.
+    /// Fill via [`GroupAttractionParams::with_groups`].
+    #[serde(default)]
+    pub groups: HashMap<usize, usize>,
+}
+impl Default for GroupAttractionParams {
+    fn default() -> Self {
+        Self {
+            c: 0.1,
+            c_repulse: 0.0,
+            groups: HashMap::new(),
+        }
+    }
+}
+
+impl GroupAttractionParams {
+    /// Groups the nodes of `g` by the key `f` returns for their payload.
+    ///
+    /// Groups are stored by node index, call again after changing the graph nodes.
+    pub fn with_groups<N, E, Ty, Ix, Dn, De, K>(
+        mut self,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        f: impl Fn(&N) -> K,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+        K: Hash + Eq,
+    {
+        let mut ids = HashMap::new();
+        self.groups = g
+            .nodes_iter()
+            .map(|(idx, n)| {
+                let next = ids.len();
+                (idx.index(), *ids.entry(f(n.payload())).or_insert(next))
+            })
+            .collect();
+        self
+    }
+}
+
+/// Clusters nodes that share a group: every grouped node is pulled towards the centroid of
+/// its group, and group centroids optionally repel each other so clusters stay apart.
+#[derive(Debug, Default)]
+pub struct GroupAttraction;
+
+impl ExtraForce for GroupAttraction {
+    type Params = GroupAttractionParams;
+
+    fn apply<N, E, Ty, Ix, Dn, De>(
+        params: &Self::Params,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        indices: &[petgraph::stable_graph::NodeIndex<Ix>],
+        disp: &mut [Vec2],
+        _area: Rect,
+        k: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if params.groups.is_empty() || (params.c == 0.0 && params.c_repulse == 0.0) {
+            return;
+        }
+        let positions = positions(g, indices);
+        let node_groups: Vec<Option<usize>> = indices
+            .iter()
+            .map(|idx| params.groups.get(&idx.index()).copied())
+            .collect();
+
+        let group_count = node_groups.iter().flatten().max().map_or(0, |&m| m + 1);
+        let mut sums = vec![(Vec2::ZERO, 0usize); group_count];
+        for (pos, group) in positions.iter().zip(&node_groups) {
+            if let Some(group) = *group {
+                sums[group].0 += pos.to_vec2();
+                sums[group].1 += 1;
+            }
+        }
+        let centroids: Vec<Option<Vec2>> = sums
+            .iter()
+            .map(|&(sum, count)| (count > 0).then(|| sum / count as f32))
+            .collect();
+
+        // Push shared by all members of a group, away from the other centroids.
+        let mut pushes = vec![Vec2::ZERO; group_count];
+        if params.c_repulse != 0.0 {
+            for a in 0..group_count {
+                let Some(ca) = centroids[a] else { continue };
+                for b in (a + 1)..group_count {
+                    let Some(cb) = centroids[b] else { continue };
+                    let delta = ca - cb;
+                    let dist = delta.length().max(1e-3);
+                    let force = delta / dist * (params.c_repulse * k * k / dist);
+                    pushes[a] += force;
+                    pushes[b] -= force;
+                }
+            }
+        }
+
+        for_each_node(&mut disp[..indices.len()], |i, d| {
+            let Some(group) = node_groups[i] else {
+                return;
+            };
+            if let Some(centroid) = centroids[group] {
+                *d += (centroid - positions[i].to_vec2()) * params.c + pushes[group];
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layouts::force_directed::extras::{CenterGravity, Extra, ExtrasTuple};
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    #[test]
+    fn pulls_towards_group_centroid_alongside_center_gravity() {
+        let mut sg: StableGraph<&str, ()> = StableGraph::new();
+        for team in ["a", "a", "b"] {
+            sg.add_node(team);
+        }
+        let mut g: Graph<&str, (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape> =
+            Graph::from(&sg);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        for (&idx, x) in indices.iter().zip([0.0, 100.0, 50.0]) {
+            g.node_mut(idx).unwrap().set_location(Pos2::new(x, 50.0));
+        }
+        // Viewport centered on (50, 50): center gravity is zero for the "b" node only.
+        let area = Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.0));
+
+        let params = GroupAttractionParams::default().with_groups(&g, |team| *team);
+        assert_eq!(params.groups.len(), 3);
+        let extras: (
+            Extra<CenterGravity, true>,
+            (Extra<GroupAttraction, true>, ()),
+        ) = (Extra::default(), (Extra::new(params), ()));
+
+        let mut disp = vec![Vec2::ZERO; 3];
+        extras.apply_all(&g, &indices, &mut disp, area, 1.0);
+
+        // Gravity 0.3 and group pull 0.1 both point at x = 50.
+        assert_eq!(disp[0], Vec2::new(20.0, 0.0));
+        assert_eq!(disp[1], Vec2::new(-20.0, 0.0));
+        assert_eq!(disp[2], Vec2::ZERO);
+    }
+}
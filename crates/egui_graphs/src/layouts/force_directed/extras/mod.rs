@@ -1,8 +1,16 @@
 mod core;
 
+pub mod bounding_box;
 pub mod center_gravity;
+pub mod collision;
+pub mod directional_flow;
+pub mod group_attraction;
 
+pub use bounding_box::{BoundingBox, BoundingBoxMode, BoundingBoxParams};
 pub use center_gravity::{CenterGravity, CenterGravityParams};
+pub use collision::{Collision, CollisionParams};
 #[allow(unused_imports)]
 pub use core::ExtraForce;
 pub use core::{Extra, ExtrasTuple};
+pub use directional_flow::{DirectionalFlow, DirectionalFlowParams};
+pub use group_attraction::{GroupAttraction, GroupAttractionParams};
@@ -0,0 +1,572 @@
+use crate::{node_size, DisplayEdge, DisplayNode, ForceAlgorithm, Graph};
+use egui::{Rect, Vec2};
+use petgraph::{
+    csr::IndexType,
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
+    EdgeType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::layouts::force_directed::implementations::fruchterman_reingold::prepare_constants;
+use crate::layouts::force_directed::quadtree::QuadTree;
+use crate::layouts::layout::AnimatedState;
+use crate::layouts::LayoutState;
+
+/// Repulsion multiplier applied to overlapping nodes when `prevent_overlap` is on.
+const OVERLAP_REPULSION: f32 = 100.0;
+/// Upper bound of a single node displacement when `prevent_overlap` is on.
+const OVERLAP_MAX_STEP: f32 = 10.0;
+
+/// Parameters and adaptive-speed state of the [`ForceAtlas2`] simulation.
+///
+/// Field names follow the original paper (Jacomy et al., 2014) and the Gephi implementation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForceAtlas2State {
+    pub is_running: bool,
+    /// Repulsion strength (`k_r`). Larger values spread the graph out.
+    pub scaling_ratio: f32,
+    /// Attraction towards the view center (`k_g`).
+    pub gravity: f32,
+    /// Gravity grows linearly with the distance to the center instead of being constant.
+    pub strong_gravity: bool,
+    /// Logarithmic attraction `log(1 + d)` which produces tighter clusters.
+    pub lin_log: bool,
+    /// Divide attraction by the source node mass so hubs are pushed to the periphery.
+    pub dissuade_hubs: bool,
+    /// Take node sizes (from `DisplayNode`) into account and repel overlapping nodes.
+    pub prevent_overlap: bool,
+    /// Amount of swinging tolerated by adaptive speed. Higher is faster but less precise.
+    pub jitter_tolerance: f32,
+    /// Approximate repulsion with a Barnes-Hut quadtree (O(n log n)).
+    pub barnes_hut: bool,
+    /// Barnes-Hut opening angle. Lower is more accurate.
+    pub theta: f32,
+    /// Current global speed chosen by adaptive speed.
+    #[serde(skip, default = "one")]
+    pub speed: f32,
+    /// Current speed efficiency chosen by adaptive speed.
+    #[serde(skip, default = "one")]
+    pub speed_efficiency: f32,
+    /// Per-node forces of the previous step, used to measure swing and traction.
+    #[serde(skip)]
+    pub prev_forces: Vec<Vec2>,
+    #[serde(skip)]
+    pub last_avg_displacement: Option<f32>,
+    /// Total number of simulation steps executed.
+    pub step_count: u64,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+impl LayoutState for ForceAtlas2State {
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        Some(self)
+    }
+}
+
+impl Default for ForceAtlas2State {
+    fn default() -> Self {
+        Self {
+            is_running: true,
+            scaling_ratio: 10.0,
+            gravity: 1.0,
+            strong_gravity: false,
+            lin_log: false,
+            dissuade_hubs: false,
+            prevent_overlap: false,
+            jitter_tolerance: 1.0,
+            barnes_hut: false,
+            theta: 1.2,
+            speed: 1.0,
+            speed_efficiency: 1.0,
+            prev_forces: Vec::new(),
+            last_avg_displacement: None,
+            step_count: 0,
+        }
+    }
+}
+
+impl AnimatedState for ForceAtlas2State {
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+    fn set_running(&mut self, v: bool) {
+        self.is_running = v;
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        self.last_avg_displacement
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.last_avg_displacement = v;
+    }
+    fn step_count(&self) -> u64 {
+        self.step_count
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.step_count = v;
+    }
+}
+
+/// Reusable per-step buffers.
+#[derive(Debug, Default)]
+pub(crate) struct Scratch {
+    forces: Vec<Vec2>,
+    positions: Vec<egui::Pos2>,
+    masses: Vec<f32>,
+    sizes: Vec<f32>,
+    tree: QuadTree,
+}
+
+/// `ForceAtlas2` continuous graph layout (Jacomy et al., 2014).
+///
+/// Repulsion is proportional to the product of node masses (degree + 1), attraction is linear
+/// (or logarithmic in LinLog mode) and the step size is chosen per node by adaptive speed
+/// based on how much each node swings between steps.
+#[derive(Debug, Default)]
+pub struct ForceAtlas2 {
+    state: ForceAtlas2State,
+    scratch: Scratch,
+}
+
+impl ForceAtlas2 {
+    pub fn from_state(state: ForceAtlas2State) -> Self {
+        Self {
+            state,
+            scratch: Scratch::default(),
+        }
+    }
+}
+
+impl ForceAlgorithm for ForceAtlas2 {
+    type State = ForceAtlas2State;
+
+    fn from_state(state: Self::State) -> Self {
+        Self {
+            state,
+            scratch: Scratch::default(),
+        }
+    }
+
+    fn step<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, view: Rect)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        step(&mut self.state, &mut self.scratch, g, view, |_, _, _, _| {});
+    }
+
+    fn state(&self) -> Self::State {
+        self.state.clone()
+    }
+}
+
+/// Advances the simulation by one step. `extras` may accumulate additional forces
+/// (same convention as `ExtrasTuple::apply_all`) before adaptive speed is applied.
+pub(crate) fn step<N, E, Ty, Ix, Dn, De>(
+    state: &mut ForceAtlas2State,
+    scratch: &mut Scratch,
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    view: Rect,
+    extras: impl FnOnce(&Graph<N, E, Ty, Ix, Dn, De>, &[NodeIndex<Ix>], &mut [Vec2], f32),
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    if !state.is_running || g.node_count() == 0 {
+        return;
+    }
+    let Some(k) = prepare_constants(view, g.node_count(), 1.0) else {
+        return;
+    };
+
+    let indices: Vec<_> = g.g().node_indices().collect();
+    prepare(g, &indices, scratch, state.prevent_overlap);
+    if state.prev_forces.len() != indices.len() {
+        state.prev_forces.clear();
+        state.prev_forces.resize(indices.len(), Vec2::ZERO);
+    }
+
+    compute_repulsion(state, scratch);
+    compute_gravity(state, scratch, view);
+    compute_attraction(state, scratch, g, &indices);
+    extras(g, &indices, &mut scratch.forces, k);
+
+    let avg = apply_adaptive_speed(state, scratch, g, &indices);
+    state.last_avg_displacement = avg;
+    state.set_step_count(state.step_count + 1);
+}
+
+fn prepare<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    scratch: &mut Scratch,
+    with_sizes: bool,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    scratch.forces.clear();
+    scratch.forces.resize(indices.len(), Vec2::ZERO);
+    scratch.positions.clear();
+    scratch.masses.clear();
+    scratch.sizes.clear();
+    for &idx in indices {
+        let n = g.node(idx).unwrap();
+        scratch.positions.push(n.location());
+        scratch
+            .masses
+            .push(g.g().neighbors_undirected(idx).count() as f32 + 1.0);
+        if with_sizes {
+            scratch
+                .sizes
+                .push(node_size(n, Vec2::X).max(node_size(n, Vec2::Y)));
+        }
+    }
+}
+
+fn repulsion_force(state: &ForceAtlas2State, delta: Vec2, m1: f32, m2: f32, sizes: f32) -> Vec2 {
+    let dist = delta.length();
+    if state.prevent_overlap {
+        let d = dist - sizes;
+        if d > 0.0 {
+            return delta * (state.scaling_ratio * m1 * m2 / (d * dist));
+        }
+        if d < 0.0 && dist > 0.0 {
+            return delta / dist * (OVERLAP_REPULSION * state.scaling_ratio * m1 * m2);
+        }
+        return Vec2::ZERO;
+    }
+    if dist > 0.0 {
+        delta * (state.scaling_ratio * m1 * m2 / (dist * dist))
+    } else {
+        Vec2::ZERO
+    }
+}
+
+fn compute_repulsion(state: &ForceAtlas2State, scratch: &mut Scratch) {
+    let n = scratch.positions.len();
+    let size_of = |i: usize| scratch.sizes.get(i).copied().unwrap_or(0.0);
+    if state.barnes_hut {
+        scratch.tree.build(&scratch.positions, &scratch.masses);
+        for i in 0..n {
+            let (m1, s1) = (scratch.masses[i], size_of(i));
+            scratch.forces[i] += scratch.tree.accumulate(i, state.theta, |delta, m2, body| {
+                let s2 = body.map_or(0.0, size_of);
+                repulsion_force(state, delta, m1, m2, s1 + s2)
+            });
+        }
+        return;
+    }
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let delta = scratch.positions[i] - scratch.positions[j];
+            let f = repulsion_force(
+                state,
+                delta,
+                scratch.masses[i],
+                scratch.masses[j],
+                size_of(i) + size_of(j),
+            );
+            scratch.forces[i] += f;
+            scratch.forces[j] -= f;
+        }
+    }
+}
+
+fn compute_gravity(state: &ForceAtlas2State, scratch: &mut Scratch, view: Rect) {
+    if state.gravity == 0.0 {
+        return;
+    }
+    let center = view.center();
+    for (i, f) in scratch.forces.iter_mut().enumerate() {
+        let delta = center - scratch.positions[i];
+        let dist = delta.length();
+        if dist == 0.0 {
+            continue;
+        }
+        let m = scratch.masses[i];
+        *f += if state.strong_gravity {
+            delta * (state.gravity * m)
+        } else {
+            delta / dist * (state.gravity * m)
+        };
+    }
+}
+
+fn compute_attraction<N, E, Ty, Ix, Dn, De>(
+    state: &ForceAtlas2State,
+    scratch: &mut Scratch,
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    // Map node index -> position in `indices`; StableGraph indices may have holes.
+    let mut pos_of = vec![usize::MAX; g.g().node_bound()];
+    for (i, idx) in indices.iter().enumerate() {
+        pos_of[idx.index()] = i;
+    }
+    let compensation = if state.dissuade_hubs {
+        scratch.masses.iter().sum::<f32>() / scratch.masses.len() as f32
+    } else {
+        1.0
+    };
+
+    for e in g.g().edge_references() {
+        let (a, b) = (pos_of[e.source().index()], pos_of[e.target().index()]);
+        if a == b {
+            continue;
+        }
+        let delta = scratch.positions[b] - scratch.positions[a];
+        let mut dist = delta.length();
+        if state.prevent_overlap {
+            dist -= scratch.sizes[a] + scratch.sizes[b];
+            if dist <= 0.0 {
+                continue;
+            }
+        }
+        if dist == 0.0 {
+            continue;
+        }
+        let mut magnitude = if state.lin_log { dist.ln_1p() } else { dist };
+        if state.dissuade_hubs {
+            magnitude *= compensation / scratch.masses[a];
+        }
+        let f = delta / delta.length() * magnitude;
+        scratch.forces[a] += f;
+        scratch.forces[b] -= f;
+    }
+}
+
+/// Chooses global and per-node speed from swing/traction and moves nodes.
+/// Returns average node displacement.
+fn apply_adaptive_speed<N, E, Ty, Ix, Dn, De>(
+    state: &mut ForceAtlas2State,
+    scratch: &Scratch,
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+) -> Option<f32>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let n = indices.len() as f32;
+    let mut total_swing = 0.0f32;
+    let mut total_traction = 0.0f32;
+    for (i, f) in scratch.forces.iter().enumerate() {
+        let prev = state.prev_forces[i];
+        let m = scratch.masses[i];
+        total_swing += m * (prev - *f).length();
+        total_traction += m * 0.5 * (prev + *f).length();
+    }
+
+    // Adaptive speed as implemented in Gephi's ForceAtlas2.
+    let estimated_jitter = 0.05 * n.sqrt();
+    let min_jitter = estimated_jitter.sqrt();
+    let max_jitter: f32 = 10.0;
+    let mut jitter = state.jitter_tolerance
+        * min_jitter.max(max_jitter.min(estimated_jitter * total_traction / (n * n)));
+    let min_speed_efficiency = 0.05;
+    if total_traction > 0.0 && total_swing / total_traction > 2.0 {
+        if state.speed_efficiency > min_speed_efficiency {
+            state.speed_efficiency *= 0.5;
+        }
+        jitter = jitter.max(state.jitter_tolerance);
+    }
+    if total_swing > 0.0 {
+        let target_speed = jitter * state.speed_efficiency * total_traction / total_swing;
+        if total_swing > jitter * total_traction {
+            if state.speed_efficiency > min_speed_efficiency {
+                state.speed_efficiency *= 0.7;
+            }
+        } else if state.speed < 1000.0 {
+            state.speed_efficiency *= 1.3;
+        }
+        let max_rise = 0.5;
+        state.speed += (target_speed - state.speed).min(max_rise * state.speed);
+    }
+
+    let mut sum = 0.0f32;
+    let mut count = 0usize;
+    for (i, &idx) in indices.iter().enumerate() {
+        let f = scratch.forces[i];
+        let swing = scratch.masses[i] * (state.prev_forces[i] - f).length();
+        let mut factor = state.speed / (1.0 + (state.speed * swing).sqrt());
+        if state.prevent_overlap {
+            let len = f.length();
+            if len > 0.0 {
+                factor = (0.1 * factor * len).min(OVERLAP_MAX_STEP) / len;
+            }
+        }
+        state.prev_forces[i] = f;
+        if g.g()[idx].pinned() {
+            continue;
+        }
+
+        let step = f * factor;
+        let new_loc = scratch.positions[i] + step;
+        if !new_loc.x.is_finite() || !new_loc.y.is_finite() {
+            continue;
+        }
+        g.g_mut()
+            .node_weight_mut(idx)
+            .unwrap()
+            .set_location(new_loc);
+        sum += step.length();
+        count += 1;
+    }
+    if count == 0 {
+        None
+    } else {
+        Some(sum / count as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_graph, DefaultEdgeShape, DefaultNodeShape};
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<
+        (),
+        (),
+        petgraph::Directed,
+        petgraph::stable_graph::DefaultIx,
+        DefaultNodeShape,
+        DefaultEdgeShape,
+    >;
+
+    fn view() -> Rect {
+        Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1000.0, 1000.0))
+    }
+
+    fn make_graph(num: usize, edges: &[(usize, usize)]) -> G {
+        let mut sg: StableGraph<(), ()> = StableGraph::default();
+        let idxs: Vec<_> = (0..num).map(|_| sg.add_node(())).collect();
+        for &(a, b) in edges {
+            sg.add_edge(idxs[a], idxs[b], ());
+        }
+        let mut g: G = to_graph(&sg);
+        let node_indices: Vec<_> = g.g().node_indices().collect();
+        for (i, idx) in node_indices.iter().enumerate() {
+            let loc = Pos2::new(
+                500.0 + (i * 37 % 17) as f32 * 3.0,
+                500.0 + (i * 11 % 13) as f32 * 3.0,
+            );
+            g.node_mut(*idx).unwrap().set_location(loc);
+        }
+        g
+    }
+
+    fn dist(g: &G, a: usize, b: usize) -> f32 {
+        let idxs: Vec<_> = g.g().node_indices().collect();
+        (g.node(idxs[a]).unwrap().location() - g.node(idxs[b]).unwrap().location()).length()
+    }
+
+    #[test]
+    fn unconnected_nodes_repel() {
+        let mut g = make_graph(2, &[]);
+        let before = dist(&g, 0, 1);
+        let mut alg = ForceAtlas2::from_state(ForceAtlas2State {
+            gravity: 0.0,
+            ..Default::default()
+        });
+        for _ in 0..10 {
+            alg.step(&mut g, view());
+        }
+        assert!(dist(&g, 0, 1) > before);
+    }
+
+    #[test]
+    fn connected_nodes_closer_than_unconnected() {
+        // 0-1 connected, 2 isolated.
+        let mut g = make_graph(3, &[(0, 1)]);
+        let mut alg = ForceAtlas2::from_state(ForceAtlas2State::default());
+        for _ in 0..300 {
+            alg.step(&mut g, view());
+        }
+        assert!(dist(&g, 0, 1) < dist(&g, 0, 2));
+        assert!(dist(&g, 0, 1) < dist(&g, 1, 2));
+    }
+
+    #[test]
+    fn prevent_overlap_separates_nodes() {
+        let mut g = make_graph(2, &[(0, 1)]);
+        let mut alg = ForceAtlas2::from_state(ForceAtlas2State {
+            prevent_overlap: true,
+            ..Default::default()
+        });
+        for _ in 0..200 {
+            alg.step(&mut g, view());
+        }
+        // Default node radius is 5, so centers must end up at least 10 apart.
+        assert!(dist(&g, 0, 1) >= 10.0);
+    }
+
+    #[test]
+    fn settles_with_adaptive_speed() {
+        let edges: Vec<_> = (1..20).map(|i| (i - 1, i)).collect();
+        let mut g = make_graph(20, &edges);
+        let mut alg = ForceAtlas2::from_state(ForceAtlas2State {
+            barnes_hut: true,
+            ..Default::default()
+        });
+        for _ in 0..500 {
+            alg.step(&mut g, view());
+        }
+        let state = alg.state();
+        assert_eq!(state.step_count, 500);
+        let avg = state.last_avg_displacement.unwrap();
+        assert!(avg.is_finite() && avg < 1.0, "avg displacement {avg}");
+    }
+
+    #[test]
+    fn keeps_moving_after_serde_round_trip() {
+        let mut g = make_graph(2, &[]);
+        let mut alg = ForceAtlas2::from_state(ForceAtlas2State {
+            gravity: 0.0,
+            ..Default::default()
+        });
+        for _ in 0..5 {
+            alg.step(&mut g, view());
+        }
+
+        let json = serde_json::to_string(&alg.state()).unwrap();
+        let state: ForceAtlas2State = serde_json::from_str(&json).unwrap();
+        assert!((state.speed - 1.0).abs() < f32::EPSILON);
+        assert!((state.speed_efficiency - 1.0).abs() < f32::EPSILON);
+
+        let mut alg = ForceAtlas2::from_state(state);
+        let before = dist(&g, 0, 1);
+        for _ in 0..5 {
+            alg.step(&mut g, view());
+        }
+        assert!(dist(&g, 0, 1) > before);
+    }
+}
@@ -0,0 +1,5 @@
+mod core;
+
+pub mod with_extras;
+
+pub use core::{ForceAtlas2, ForceAtlas2State};
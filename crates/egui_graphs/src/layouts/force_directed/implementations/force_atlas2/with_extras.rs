@@ -0,0 +1,103 @@
+use crate::{
+    layouts::force_directed::extras::ExtrasTuple, CenterGravity, DisplayEdge, DisplayNode, Extra,
+    ForceAlgorithm, Graph,
+};
+use egui::Rect;
+use petgraph::EdgeType;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::core::{step, ForceAtlas2State, Scratch};
+use crate::layouts::layout::AnimatedState;
+use crate::layouts::LayoutState;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(bound(serialize = "E: Serialize", deserialize = "E: DeserializeOwned"))]
+pub struct ForceAtlas2WithExtrasState<E: ExtrasTuple> {
+    pub base: ForceAtlas2State,
+    pub extras: E,
+}
+impl<E: ExtrasTuple> LayoutState for ForceAtlas2WithExtrasState<E> {
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        Some(self)
+    }
+}
+
+impl<E: ExtrasTuple> AnimatedState for ForceAtlas2WithExtrasState<E> {
+    fn is_running(&self) -> bool {
+        self.base.is_running
+    }
+    fn set_running(&mut self, v: bool) {
+        self.base.is_running = v;
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        self.base.last_avg_displacement
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.base.last_avg_displacement = v;
+    }
+    fn step_count(&self) -> u64 {
+        self.base.step_count
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.base.step_count = v;
+    }
+}
+
+/// [`super::ForceAtlas2`] with composable extra forces applied before adaptive speed.
+#[derive(Debug, Default)]
+pub struct ForceAtlas2WithExtras<E: ExtrasTuple> {
+    state: ForceAtlas2WithExtrasState<E>,
+    scratch: Scratch,
+}
+
+impl<E: ExtrasTuple> ForceAtlas2WithExtras<E> {
+    pub fn from_state(state: ForceAtlas2WithExtrasState<E>) -> Self {
+        Self {
+            state,
+            scratch: Scratch::default(),
+        }
+    }
+}
+
+impl<E: ExtrasTuple> ForceAlgorithm for ForceAtlas2WithExtras<E> {
+    type State = ForceAtlas2WithExtrasState<E>;
+
+    fn from_state(state: Self::State) -> Self {
+        Self {
+            state,
+            scratch: Scratch::default(),
+        }
+    }
+
+    fn step<N, Ed, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, Ed, Ty, Ix, Dn, De>, view: Rect)
+    where
+        N: Clone,
+        Ed: Clone,
+        Ty: EdgeType,
+        Ix: petgraph::csr::IndexType,
+        Dn: DisplayNode<N, Ed, Ty, Ix>,
+        De: DisplayEdge<N, Ed, Ty, Ix, Dn>,
+    {
+        let extras = &self.state.extras;
+        step(
+            &mut self.state.base,
+            &mut self.scratch,
+            g,
+            view,
+            |g, indices, forces, k| extras.apply_all(g, indices, forces, view, k),
+        );
+        if self.state.base.is_running {
+            let indices: Vec<_> = g.g().node_indices().collect();
+            self.state.extras.constrain_all(g, &indices, view);
+        }
+    }
+
+    fn state(&self) -> Self::State {
+        self.state.clone()
+    }
+}
+
+/// Convenience aliases when only center gravity is desired.
+pub type ForceAtlas2WithCenterGravity = ForceAtlas2WithExtras<(Extra<CenterGravity, true>, ())>;
+pub type ForceAtlas2WithCenterGravityState =
+    ForceAtlas2WithExtrasState<(Extra<CenterGravity, true>, ())>;
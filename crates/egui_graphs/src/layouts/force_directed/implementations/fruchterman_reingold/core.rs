@@ -1,11 +1,34 @@
+use std::collections::HashMap;
+
 use crate::{DisplayEdge, DisplayNode, ForceAlgorithm, Graph};
-use egui::{Rect, Vec2};
-use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+use egui::{Pos2, Rect, Vec2};
+use petgraph::{
+    csr::IndexType,
+    stable_graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+    Direction::{Incoming, Outgoing},
+    EdgeType,
+};
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "parallel")]
+use crate::layouts::force_directed::parallel::use_parallel;
+use crate::layouts::force_directed::parallel::{for_each_node, positions};
+use crate::layouts::force_directed::quadtree::QuadTree;
 use crate::layouts::layout::AnimatedState;
 use crate::layouts::LayoutState;
 
+/// How several edges between the same pair of nodes (see [`crate::EdgeProps::order`]) attract.
+/// Edges in both directions of a directed graph connect the same pair too.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ParallelEdges {
+    /// The pair is pulled together once, by the heaviest of its edges.
+    #[default]
+    Max,
+    /// Every edge pulls on its own, so the weights of parallel edges add up.
+    Sum,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FruchtermanReingoldState {
     pub is_running: bool,
@@ -16,13 +39,55 @@ pub struct FruchtermanReingoldState {
     pub k_scale: f32,
     pub c_attract: f32,
     pub c_repulse: f32,
+    /// Approximate repulsion with a Barnes-Hut quadtree (O(n log n)) instead of the exact O(n²) sum.
+    #[serde(default)]
+    pub barnes_hut: bool,
+    /// Barnes-Hut opening angle. Lower is more accurate, `0.0` is exact. Typical range `0.5..=1.2`.
+    #[serde(default = "default_theta")]
+    pub theta: f32,
+    /// Attraction multipliers keyed by edge index. Missing edges count as `1.0`, entries of
+    /// removed edges are dropped by the next step.
+    /// Fill via [`FruchtermanReingoldState::with_edge_weights`].
+    #[serde(default)]
+    pub edge_weights: HashMap<usize, f32>,
+    #[serde(default)]
+    pub parallel_edges: ParallelEdges,
+    /// Place nodes whose location was never set near the centroid of their placed neighbours.
+    /// Only those nodes and their neighbours then move at full speed ("warm") for
+    /// `warm_steps` steps while the rest of the layout is slowed down by `cool`.
+    #[serde(default)]
+    pub incremental: bool,
+    #[serde(default = "default_warm_steps")]
+    pub warm_steps: u32,
+    /// Step multiplier of the nodes that are not warm while some nodes are.
+    #[serde(default = "default_cool")]
+    pub cool: f32,
+    /// Remaining warm steps keyed by node index.
+    #[serde(default)]
+    pub warm: HashMap<usize, u32>,
     #[serde(skip)]
     pub last_avg_displacement: Option<f32>,
     /// Total number of simulation steps executed.
     pub step_count: u64,
 }
 
-impl LayoutState for FruchtermanReingoldState {}
+impl LayoutState for FruchtermanReingoldState {
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        Some(self)
+    }
+}
+
+fn default_theta() -> f32 {
+    0.8
+}
+
+fn default_warm_steps() -> u32 {
+    60
+}
+
+fn default_cool() -> f32 {
+    0.05
+}
 
 impl Default for FruchtermanReingoldState {
     fn default() -> Self {
@@ -35,6 +100,14 @@ impl Default for FruchtermanReingoldState {
             k_scale: 1.0,
             c_attract: 1.0,
             c_repulse: 1.0,
+            barnes_hut: false,
+            theta: default_theta(),
+            edge_weights: HashMap::new(),
+            parallel_edges: ParallelEdges::Max,
+            incremental: false,
+            warm_steps: default_warm_steps(),
+            cool: default_cool(),
+            warm: HashMap::new(),
             last_avg_displacement: None,
             step_count: 0,
         }
@@ -63,10 +136,48 @@ impl FruchtermanReingoldState {
             k_scale,
             c_attract,
             c_repulse,
+            barnes_hut: false,
+            theta: default_theta(),
+            edge_weights: HashMap::new(),
+            parallel_edges: ParallelEdges::Max,
+            incremental: false,
+            warm_steps: default_warm_steps(),
+            cool: default_cool(),
+            warm: HashMap::new(),
             last_avg_displacement: None,
             step_count: 0,
         }
     }
+
+    /// Uses `f` over every edge payload of `g` as an attraction multiplier: heavier edges
+    /// pull harder and end up shorter. Non-positive or non-finite values are ignored.
+    ///
+    /// Payloads are read only here and weights are keyed by edge index: call it again
+    /// after adding edges or changing their payloads. Weights of removed edges are dropped
+    /// by the next step, while an edge added in their place before that step takes them
+    /// over.
+    pub fn with_edge_weights<N, E, Ty, Ix, Dn, De>(
+        mut self,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        f: impl Fn(&E) -> f32,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.edge_weights = g
+            .edges_iter()
+            .filter_map(|(idx, e)| {
+                let w = f(e.payload());
+                (w.is_finite() && w > 0.0).then_some((idx.index(), w))
+            })
+            .collect();
+        self
+    }
 }
 
 // Step counting is provided via AnimatedState default methods and field in this state.
@@ -97,6 +208,8 @@ pub struct FruchtermanReingold {
     state: FruchtermanReingoldState,
     // Reusable displacement buffer to avoid per-frame allocations
     scratch_disp: Vec<Vec2>,
+    // Reusable quadtree for Barnes-Hut repulsion
+    scratch_tree: QuadTree,
 }
 
 impl FruchtermanReingold {
@@ -104,6 +217,7 @@ impl FruchtermanReingold {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 }
@@ -115,6 +229,7 @@ impl ForceAlgorithm for FruchtermanReingold {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 
@@ -131,11 +246,16 @@ impl ForceAlgorithm for FruchtermanReingold {
             return;
         }
 
-        let params = &self.state;
         // Always compute k from the viewport area for stability and simplicity.
-        let Some(k) = prepare_constants(view, g.node_count(), params.k_scale) else {
+        let Some(k) = prepare_constants(view, g.node_count(), self.state.k_scale) else {
             return;
         };
+        if self.state.incremental {
+            place_new_nodes(g, &mut self.state.warm, self.state.warm_steps, k);
+        }
+        drop_stale_weights(g, &mut self.state.edge_weights);
+
+        let params = &self.state;
 
         let indices: Vec<_> = g.g().node_indices().collect();
         // Ensure scratch buffer is sized and zeroed
@@ -145,29 +265,50 @@ impl ForceAlgorithm for FruchtermanReingold {
             self.scratch_disp.resize(indices.len(), Vec2::ZERO);
         }
 
-        compute_repulsion(
+        if params.barnes_hut {
+            compute_repulsion_barnes_hut(
+                g,
+                &indices,
+                &mut self.scratch_disp,
+                &mut self.scratch_tree,
+                k,
+                params.epsilon,
+                params.c_repulse,
+                params.theta,
+            );
+        } else {
+            compute_repulsion(
+                g,
+                &indices,
+                &mut self.scratch_disp,
+                k,
+                params.epsilon,
+                params.c_repulse,
+            );
+        }
+        compute_attraction(
             g,
             &indices,
             &mut self.scratch_disp,
             k,
             params.epsilon,
-            params.c_repulse,
+            params.c_attract,
+            &params.edge_weights,
+            params.parallel_edges,
         );
-        compute_attraction(
-            g,
+        cool_down(
             &indices,
             &mut self.scratch_disp,
-            k,
-            params.epsilon,
-            params.c_attract,
+            &mut self.state.warm,
+            self.state.cool,
         );
         let avg = apply_displacements(
             g,
             &indices,
             &self.scratch_disp,
-            params.dt,
-            params.damping,
-            params.max_step,
+            self.state.dt,
+            self.state.damping,
+            self.state.max_step,
         );
         self.state.last_avg_displacement = avg;
         self.state.set_step_count(self.state.step_count + 1);
@@ -207,20 +348,101 @@ pub(crate) fn compute_repulsion<N, E, Ty, Ix, Dn, De>(
     Dn: DisplayNode<N, E, Ty, Ix>,
     De: DisplayEdge<N, E, Ty, Ix, Dn>,
 {
-    for i in 0..indices.len() {
-        for j in (i + 1)..indices.len() {
-            let (idx_i, idx_j) = (indices[i], indices[j]);
-            let delta = g.g().node_weight(idx_i).unwrap().location()
-                - g.g().node_weight(idx_j).unwrap().location();
-            let distance = delta.length().max(epsilon);
-            let force = c_repulse * (k * k) / distance;
-            let dir = delta / distance;
-            disp[i] += dir * force;
-            disp[j] -= dir * force;
+    let positions = positions(g, indices);
+    #[cfg(feature = "parallel")]
+    if use_parallel(positions.len()) {
+        repulsion_per_node(&positions, disp, k, epsilon, c_repulse);
+        return;
+    }
+    repulsion_pairwise(&positions, disp, k, epsilon, c_repulse);
+}
+
+/// Repulsion force of `b` acting on `a`.
+fn repulsion(a: Pos2, b: Pos2, k: f32, epsilon: f32, c_repulse: f32) -> Vec2 {
+    let delta = a - b;
+    let distance = delta.length().max(epsilon);
+    let force = c_repulse * (k * k) / distance;
+    delta / distance * force
+}
+
+/// Visits every pair once and applies the force to both nodes.
+fn repulsion_pairwise(positions: &[Pos2], disp: &mut [Vec2], k: f32, epsilon: f32, c_repulse: f32) {
+    for i in 0..positions.len() {
+        for j in (i + 1)..positions.len() {
+            let f = repulsion(positions[i], positions[j], k, epsilon, c_repulse);
+            disp[i] += f;
+            disp[j] -= f;
         }
     }
 }
 
+/// Same sums as [`repulsion_pairwise`], added in the same order, but every node computes its
+/// own so nodes can be processed in parallel. Twice the work, bit-identical result.
+#[cfg(feature = "parallel")]
+fn repulsion_per_node(positions: &[Pos2], disp: &mut [Vec2], k: f32, epsilon: f32, c_repulse: f32) {
+    for_each_node(disp, |i, d| {
+        for j in 0..positions.len() {
+            if j < i {
+                *d -= repulsion(positions[j], positions[i], k, epsilon, c_repulse);
+            } else if j > i {
+                *d += repulsion(positions[i], positions[j], k, epsilon, c_repulse);
+            }
+        }
+    });
+}
+
+/// Barnes-Hut approximation of [`compute_repulsion`]. With `theta == 0.0` the result
+/// matches the exact pairwise sum (up to float summation order).
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn compute_repulsion_barnes_hut<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    disp: &mut [Vec2],
+    tree: &mut QuadTree,
+    k: f32,
+    epsilon: f32,
+    c_repulse: f32,
+    theta: f32,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let positions = positions(g, indices);
+    let masses = vec![1.0; positions.len()];
+    tree.build(&positions, &masses);
+
+    let k2 = k * k;
+    let tree = &*tree;
+    for_each_node(&mut disp[..indices.len()], |i, d| {
+        *d += tree.accumulate(i, theta, |delta, mass, _| {
+            let distance = delta.length().max(epsilon);
+            delta / distance * (c_repulse * k2 * mass / distance)
+        });
+    });
+}
+
+/// Drops the weights of edges no longer in `g`.
+pub(crate) fn drop_stale_weights<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    weights: &mut HashMap<usize, f32>,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    if !weights.is_empty() {
+        weights.retain(|&e, _| g.g().edge_weight(EdgeIndex::new(e)).is_some());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn compute_attraction<N, E, Ty, Ix, Dn, De>(
     g: &Graph<N, E, Ty, Ix, Dn, De>,
     indices: &[NodeIndex<Ix>],
@@ -228,6 +450,8 @@ pub(crate) fn compute_attraction<N, E, Ty, Ix, Dn, De>(
     k: f32,
     epsilon: f32,
     c_attract: f32,
+    weights: &HashMap<usize, f32>,
+    parallel_edges: ParallelEdges,
 ) where
     N: Clone,
     E: Clone,
@@ -236,15 +460,135 @@ pub(crate) fn compute_attraction<N, E, Ty, Ix, Dn, De>(
     Dn: DisplayNode<N, E, Ty, Ix>,
     De: DisplayEdge<N, E, Ty, Ix, Dn>,
 {
-    for (vec_pos, &idx) in indices.iter().enumerate() {
-        let loc = g.g().node_weight(idx).unwrap().location();
-        for nbr in g.g().neighbors_undirected(idx) {
-            let delta = g.g().node_weight(nbr).unwrap().location() - loc;
+    // Neighbour locations and weights of every node, flattened.
+    let mut offsets = Vec::with_capacity(indices.len() + 1);
+    let mut neighbors: Vec<(Pos2, f32)> = Vec::new();
+    let mut incident: Vec<(NodeIndex<Ix>, f32)> = Vec::new();
+    offsets.push(0);
+    for &idx in indices {
+        incident.clear();
+        let weight = |e: usize| weights.get(&e).copied().unwrap_or(1.0);
+        incident.extend(g.g().edges_directed(idx, Outgoing).map(|e| {
+            let other = if e.source() == idx {
+                e.target()
+            } else {
+                e.source()
+            };
+            (other, weight(e.id().index()))
+        }));
+        if g.is_directed() {
+            incident.extend(
+                g.g()
+                    .edges_directed(idx, Incoming)
+                    .map(|e| (e.source(), weight(e.id().index()))),
+            );
+        }
+        if parallel_edges == ParallelEdges::Max {
+            incident.sort_by_key(|&(other, _)| other);
+            incident.dedup_by(|next, kept| {
+                let same = next.0 == kept.0;
+                if same {
+                    kept.1 = kept.1.max(next.1);
+                }
+                same
+            });
+        }
+        neighbors.extend(
+            incident
+                .iter()
+                .map(|&(other, w)| (g.g().node_weight(other).unwrap().location(), w)),
+        );
+        offsets.push(neighbors.len());
+    }
+    let positions = positions(g, indices);
+
+    for_each_node(&mut disp[..indices.len()], |i, d| {
+        for &(nbr, w) in &neighbors[offsets[i]..offsets[i + 1]] {
+            let delta = nbr - positions[i];
             let distance = delta.length().max(epsilon);
-            let force = c_attract * (distance * distance) / k;
-            disp[vec_pos] += (delta / distance) * force;
+            let force = w * c_attract * (distance * distance) / k;
+            *d += (delta / distance) * force;
         }
+    });
+}
+
+/// Places every node without a location near the centroid of its placed neighbours, or of
+/// all placed nodes if it has none, and keeps it and its neighbours warm for `warm_steps`.
+/// Nodes are offset from the centroid by half the ideal edge length at a golden-angle step
+/// per index, so nodes added together do not coincide.
+pub(crate) fn place_new_nodes<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    warm: &mut HashMap<usize, u32>,
+    warm_steps: u32,
+    k: f32,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let new: Vec<_> = g
+        .nodes_iter()
+        .filter(|(_, n)| !n.location_set())
+        .map(|(idx, _)| idx)
+        .collect();
+    if new.is_empty() {
+        return;
     }
+    for idx in new {
+        let neighbors: Vec<_> = g.g().neighbors_undirected(idx).collect();
+        let around = centroid(
+            neighbors
+                .iter()
+                .map(|&nbr| &g.g()[nbr])
+                .filter(|n| n.location_set())
+                .map(crate::Node::location),
+        )
+        .or_else(|| {
+            centroid(
+                g.nodes_iter()
+                    .filter(|(_, n)| n.location_set())
+                    .map(|(_, n)| n.location()),
+            )
+        })
+        .unwrap_or(Pos2::ZERO);
+        let offset = Vec2::angled(idx.index() as f32 * 2.399_963) * (k * 0.5);
+        g.g_mut()[idx].set_location(around + offset);
+
+        warm.insert(idx.index(), warm_steps);
+        for nbr in neighbors {
+            warm.insert(nbr.index(), warm_steps);
+        }
+    }
+}
+
+fn centroid(points: impl Iterator<Item = Pos2>) -> Option<Pos2> {
+    let (sum, count) = points.fold((Vec2::ZERO, 0usize), |(s, c), p| (s + p.to_vec2(), c + 1));
+    (count > 0).then(|| (sum / count as f32).to_pos2())
+}
+
+/// Slows down every node that is not warm by `cool` while some nodes are warm, then counts
+/// down the remaining warm steps.
+pub(crate) fn cool_down<Ix: IndexType>(
+    indices: &[NodeIndex<Ix>],
+    disp: &mut [Vec2],
+    warm: &mut HashMap<usize, u32>,
+    cool: f32,
+) {
+    if warm.is_empty() {
+        return;
+    }
+    for (d, idx) in disp.iter_mut().zip(indices) {
+        if !warm.contains_key(&idx.index()) {
+            *d *= cool;
+        }
+    }
+    warm.retain(|_, steps| {
+        *steps = steps.saturating_sub(1);
+        *steps > 0
+    });
 }
 
 pub(crate) fn apply_displacements<N, E, Ty, Ix, Dn, De>(
@@ -269,6 +613,9 @@ where
     let mut sum = 0.0f32;
     let mut count = 0usize;
     for (vec_pos, &idx) in indices.iter().enumerate() {
+        if g.g()[idx].pinned() {
+            continue;
+        }
         let mut step = disp[vec_pos] * dt * damping;
         let len = step.length();
         if len > max_step {
@@ -362,6 +709,72 @@ mod tests {
         assert!((b.x - a.x).abs() > 1.0, "Nodes should move apart");
     }
 
+    #[test]
+    fn pinned_node_stays_in_place() {
+        let mut g = make_graph(2);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        g.g_mut()[indices[0]].set_pinned(true);
+        let before = g.g()[indices[0]].location();
+        let rect = empty_ui_rect();
+        let params = FruchtermanReingoldState::default();
+        let k = prepare_constants(rect, 2, params.k_scale).unwrap();
+        let mut disp = vec![Vec2::ZERO; indices.len()];
+        compute_repulsion(&g, &indices, &mut disp, k, params.epsilon, params.c_repulse);
+        apply_displacements(
+            &mut g,
+            &indices,
+            &disp,
+            params.dt,
+            params.damping,
+            params.max_step,
+        );
+        assert_eq!(g.g()[indices[0]].location(), before);
+        assert!(
+            g.g()[indices[1]].location().x > 10.0,
+            "Free node is pushed away"
+        );
+    }
+
+    #[test]
+    fn incremental_places_new_nodes_and_keeps_the_rest_cool() {
+        let mut g = make_graph(3);
+        let idxs: Vec<_> = g.g().node_indices().collect();
+        g.g_mut()[idxs[1]].set_location(Pos2::new(100.0, 0.0));
+        g.g_mut()[idxs[2]].set_location(Pos2::new(1000.0, 1000.0));
+        let added = g.add_node(());
+        g.add_edge(idxs[0], added, ());
+        g.add_edge(idxs[1], added, ());
+
+        let mut warm = HashMap::new();
+        place_new_nodes(&mut g, &mut warm, 2, 20.0);
+        let loc = g.g()[added].location();
+        assert!(g.g()[added].location_set());
+        assert!((loc.distance(Pos2::new(50.0, 0.0)) - 10.0).abs() < 1e-3);
+        assert_eq!(warm.len(), 3);
+        assert!(!warm.contains_key(&idxs[2].index()));
+
+        let indices: Vec<_> = g.g().node_indices().collect();
+        let mut disp = vec![Vec2::splat(1.0); indices.len()];
+        cool_down(&indices, &mut disp, &mut warm, 0.1);
+        assert_eq!(disp[2], Vec2::splat(0.1));
+        assert_eq!(disp[3], Vec2::splat(1.0));
+        cool_down(&indices, &mut disp, &mut warm, 0.1);
+        assert!(warm.is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_repulsion_matches_sequential() {
+        let positions: Vec<_> = (0..600)
+            .map(|i| Pos2::new((i * 37 % 211) as f32 * 4.0, (i * 91 % 199) as f32 * 4.0))
+            .collect();
+        let mut sequential = vec![Vec2::ZERO; positions.len()];
+        let mut parallel = vec![Vec2::ZERO; positions.len()];
+        repulsion_pairwise(&positions, &mut sequential, 30.0, 1e-3, 1.0);
+        repulsion_per_node(&positions, &mut parallel, 30.0, 1e-3, 1.0);
+        assert_eq!(sequential, parallel);
+    }
+
     #[test]
     fn attraction_decreases_distance_when_far() {
         let mut g = make_graph(2);
@@ -382,7 +795,16 @@ mod tests {
         let mut disp = vec![Vec2::ZERO; indices.len()];
         let start_dist = 1200.0;
         compute_repulsion(&g, &indices, &mut disp, k, params.epsilon, params.c_repulse);
-        compute_attraction(&g, &indices, &mut disp, k, params.epsilon, params.c_attract);
+        compute_attraction(
+            &g,
+            &indices,
+            &mut disp,
+            k,
+            params.epsilon,
+            params.c_attract,
+            &params.edge_weights,
+            params.parallel_edges,
+        );
         apply_displacements(
             &mut g,
             &indices,
@@ -399,4 +821,160 @@ mod tests {
             "Distance should shrink due to attraction"
         );
     }
+
+    #[test]
+    fn attraction_scales_with_weight_and_parallel_edges() {
+        let attraction = |weights: &[f32], parallel_edges: ParallelEdges| {
+            let mut g = make_graph(2);
+            let idxs: Vec<_> = g.g().node_indices().collect();
+            let edge_weights = weights
+                .iter()
+                .map(|&w| (g.add_edge(idxs[0], idxs[1], ()).index(), w))
+                .collect();
+            let params = FruchtermanReingoldState {
+                edge_weights,
+                parallel_edges,
+                ..FruchtermanReingoldState::default()
+            };
+            let mut disp = vec![Vec2::ZERO; 2];
+            compute_attraction(
+                &g,
+                &idxs,
+                &mut disp,
+                10.0,
+                params.epsilon,
+                params.c_attract,
+                &params.edge_weights,
+                params.parallel_edges,
+            );
+            disp[0].x
+        };
+
+        let single = attraction(&[1.0], ParallelEdges::Max);
+        assert!(single > 0.0);
+        assert!((attraction(&[3.0], ParallelEdges::Max) - 3.0 * single).abs() < 1e-3);
+        assert!((attraction(&[1.0, 2.0], ParallelEdges::Max) - 2.0 * single).abs() < 1e-3);
+        assert!((attraction(&[1.0, 2.0], ParallelEdges::Sum) - 3.0 * single).abs() < 1e-3);
+    }
+
+    #[test]
+    fn step_drops_weights_of_removed_edges() {
+        let mut g = make_graph(3);
+        let idxs: Vec<_> = g.g().node_indices().collect();
+        let kept = g.add_edge(idxs[0], idxs[1], ());
+        let removed = g.add_edge(idxs[1], idxs[2], ());
+        let state = FruchtermanReingoldState::default().with_edge_weights(&g, |()| 2.0);
+        assert_eq!(state.edge_weights.len(), 2);
+
+        g.remove_edge(removed);
+        let mut alg = FruchtermanReingold::from_state(state);
+        alg.step(&mut g, empty_ui_rect());
+        let weights = alg.state().edge_weights;
+        assert_eq!(
+            weights.keys().copied().collect::<Vec<_>>(),
+            vec![kept.index()]
+        );
+    }
+    #[test]
+    fn repulsion_barnes_hut_increases_distance() {
+        let mut g = make_graph(2);
+        let idxs: Vec<_> = g.g().node_indices().collect();
+        g.g_mut()
+            .node_weight_mut(idxs[0])
+            .unwrap()
+            .set_location(Pos2::new(0.0, 0.0));
+        g.g_mut()
+            .node_weight_mut(idxs[1])
+            .unwrap()
+            .set_location(Pos2::new(1.0, 0.0));
+        let rect = empty_ui_rect();
+        let params = FruchtermanReingoldState::default();
+        let k = prepare_constants(rect, 2, params.k_scale).unwrap();
+        let indices: Vec<_> = g.g().node_indices().collect();
+        let mut disp = vec![Vec2::ZERO; indices.len()];
+        let mut tree = QuadTree::default();
+        compute_repulsion_barnes_hut(
+            &g,
+            &indices,
+            &mut disp,
+            &mut tree,
+            k,
+            params.epsilon,
+            params.c_repulse,
+            params.theta,
+        );
+        apply_displacements(
+            &mut g,
+            &indices,
+            &disp,
+            params.dt,
+            params.damping,
+            params.max_step,
+        );
+        let a = g.g().node_weight(indices[0]).unwrap().location();
+        let b = g.g().node_weight(indices[1]).unwrap().location();
+        assert!((b.x - a.x).abs() > 1.0, "Nodes should move apart");
+    }
+
+    #[test]
+    fn barnes_hut_repulsion_close_to_exact() {
+        let mut g = make_graph(200);
+        let indices: Vec<_> = g.g().node_indices().collect();
+        for (i, idx) in indices.iter().enumerate() {
+            let (x, y) = ((i * 37 % 211) as f32 * 4.0, (i * 91 % 199) as f32 * 4.0);
+            g.g_mut()
+                .node_weight_mut(*idx)
+                .unwrap()
+                .set_location(Pos2::new(x, y));
+        }
+        let params = FruchtermanReingoldState::default();
+        let k = prepare_constants(empty_ui_rect(), indices.len(), params.k_scale).unwrap();
+
+        let mut exact = vec![Vec2::ZERO; indices.len()];
+        compute_repulsion(
+            &g,
+            &indices,
+            &mut exact,
+            k,
+            params.epsilon,
+            params.c_repulse,
+        );
+
+        let mut tree = QuadTree::default();
+        let mut approx_exact = vec![Vec2::ZERO; indices.len()];
+        compute_repulsion_barnes_hut(
+            &g,
+            &indices,
+            &mut approx_exact,
+            &mut tree,
+            k,
+            params.epsilon,
+            params.c_repulse,
+            0.0,
+        );
+        let mut approx = vec![Vec2::ZERO; indices.len()];
+        compute_repulsion_barnes_hut(
+            &g,
+            &indices,
+            &mut approx,
+            &mut tree,
+            k,
+            params.epsilon,
+            params.c_repulse,
+            params.theta,
+        );
+
+        let (mut err_exact, mut err, mut total) = (0.0f32, 0.0f32, 0.0f32);
+        for i in 0..indices.len() {
+            err_exact += (exact[i] - approx_exact[i]).length();
+            err += (exact[i] - approx[i]).length();
+            total += exact[i].length();
+        }
+        assert!(err_exact / total < 1e-4, "theta = 0 should be exact");
+        assert!(
+            err / total < 0.05,
+            "relative error too large: {}",
+            err / total
+        );
+    }
 }
@@ -2,4 +2,5 @@ mod core;
 
 pub mod with_extras;
 
-pub use core::{FruchtermanReingold, FruchtermanReingoldState};
+pub(crate) use core::prepare_constants;
+pub use core::{FruchtermanReingold, FruchtermanReingoldState, ParallelEdges};
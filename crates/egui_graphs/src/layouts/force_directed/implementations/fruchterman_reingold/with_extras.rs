@@ -7,9 +7,10 @@ use petgraph::EdgeType;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use super::core::{
-    apply_displacements, compute_attraction, compute_repulsion, prepare_constants,
-    FruchtermanReingoldState,
+    apply_displacements, compute_attraction, compute_repulsion, compute_repulsion_barnes_hut,
+    cool_down, drop_stale_weights, place_new_nodes, prepare_constants, FruchtermanReingoldState,
 };
+use crate::layouts::force_directed::quadtree::QuadTree;
 use crate::layouts::layout::AnimatedState;
 use crate::layouts::LayoutState;
 
@@ -19,7 +20,11 @@ pub struct FruchtermanReingoldWithExtrasState<E: ExtrasTuple> {
     pub base: FruchtermanReingoldState,
     pub extras: E,
 }
-impl<E: ExtrasTuple> LayoutState for FruchtermanReingoldWithExtrasState<E> {}
+impl<E: ExtrasTuple> LayoutState for FruchtermanReingoldWithExtrasState<E> {
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        Some(self)
+    }
+}
 
 impl<E: ExtrasTuple> AnimatedState for FruchtermanReingoldWithExtrasState<E> {
     fn is_running(&self) -> bool {
@@ -47,6 +52,8 @@ pub struct FruchtermanReingoldWithExtras<E: ExtrasTuple> {
     state: FruchtermanReingoldWithExtrasState<E>,
     // Reusable displacement buffer
     scratch_disp: Vec<Vec2>,
+    // Reusable quadtree for Barnes-Hut repulsion
+    scratch_tree: QuadTree,
 }
 
 impl<E: ExtrasTuple> FruchtermanReingoldWithExtras<E> {
@@ -54,6 +61,7 @@ impl<E: ExtrasTuple> FruchtermanReingoldWithExtras<E> {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 }
@@ -65,6 +73,7 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
         Self {
             state,
             scratch_disp: Vec::new(),
+            scratch_tree: QuadTree::default(),
         }
     }
 
@@ -80,11 +89,17 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
         if g.node_count() == 0 || !self.state.base.is_running {
             return;
         }
-        let base = &self.state.base;
         let area_rect = view;
-        let Some(k) = prepare_constants(view, g.node_count(), base.k_scale) else {
+        let Some(k) = prepare_constants(view, g.node_count(), self.state.base.k_scale) else {
             return;
         };
+        if self.state.base.incremental {
+            let base = &mut self.state.base;
+            place_new_nodes(g, &mut base.warm, base.warm_steps, k);
+        }
+        drop_stale_weights(g, &mut self.state.base.edge_weights);
+
+        let base = &self.state.base;
 
         let indices: Vec<_> = g.g().node_indices().collect();
         if self.scratch_disp.len() == indices.len() {
@@ -93,14 +108,27 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
             self.scratch_disp.resize(indices.len(), Vec2::ZERO);
         }
 
-        compute_repulsion(
-            g,
-            &indices,
-            &mut self.scratch_disp,
-            k,
-            base.epsilon,
-            base.c_repulse,
-        );
+        if base.barnes_hut {
+            compute_repulsion_barnes_hut(
+                g,
+                &indices,
+                &mut self.scratch_disp,
+                &mut self.scratch_tree,
+                k,
+                base.epsilon,
+                base.c_repulse,
+                base.theta,
+            );
+        } else {
+            compute_repulsion(
+                g,
+                &indices,
+                &mut self.scratch_disp,
+                k,
+                base.epsilon,
+                base.c_repulse,
+            );
+        }
         compute_attraction(
             g,
             &indices,
@@ -108,12 +136,21 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
             k,
             base.epsilon,
             base.c_attract,
+            &base.edge_weights,
+            base.parallel_edges,
         );
 
         self.state
             .extras
             .apply_all(g, &indices, &mut self.scratch_disp, area_rect, k);
+        cool_down(
+            &indices,
+            &mut self.scratch_disp,
+            &mut self.state.base.warm,
+            self.state.base.cool,
+        );
 
+        let base = &self.state.base;
         let avg = apply_displacements(
             g,
             &indices,
@@ -122,6 +159,7 @@ impl<E: ExtrasTuple> ForceAlgorithm for FruchtermanReingoldWithExtras<E> {
             base.damping,
             base.max_step,
         );
+        self.state.extras.constrain_all(g, &indices, area_rect);
         self.state.base.last_avg_displacement = avg;
         self.state
             .base
@@ -1 +1,2 @@
+pub mod force_atlas2;
 pub mod fruchterman_reingold;
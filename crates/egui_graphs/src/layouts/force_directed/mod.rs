@@ -1,14 +1,31 @@
 mod algorithm;
+mod background;
 mod implementations;
 mod layout;
+mod parallel;
+mod quadtree;
+#[cfg(not(target_arch = "wasm32"))]
+mod worker;
 
 pub mod extras;
 
 pub use algorithm::ForceAlgorithm;
-pub use extras::{CenterGravity, CenterGravityParams, Extra};
+pub use background::{ForceDirectedBackground, ForceDirectedBackgroundState};
+pub use extras::{
+    BoundingBox, BoundingBoxMode, BoundingBoxParams, CenterGravity, CenterGravityParams, Collision,
+    CollisionParams, DirectionalFlow, DirectionalFlowParams, Extra, GroupAttraction,
+    GroupAttractionParams,
+};
+pub use implementations::force_atlas2::with_extras::{
+    ForceAtlas2WithCenterGravity, ForceAtlas2WithCenterGravityState, ForceAtlas2WithExtras,
+    ForceAtlas2WithExtrasState,
+};
+pub use implementations::force_atlas2::{ForceAtlas2, ForceAtlas2State};
 pub use implementations::fruchterman_reingold::with_extras::{
     FruchtermanReingoldWithCenterGravity, FruchtermanReingoldWithCenterGravityState,
     FruchtermanReingoldWithExtras, FruchtermanReingoldWithExtrasState,
 };
-pub use implementations::fruchterman_reingold::{FruchtermanReingold, FruchtermanReingoldState};
+pub use implementations::fruchterman_reingold::{
+    FruchtermanReingold, FruchtermanReingoldState, ParallelEdges,
+};
 pub use layout::ForceDirected;
@@ -0,0 +1,51 @@
+use egui::Pos2;
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, EdgeType};
+
+use crate::{DisplayEdge, DisplayNode, Graph};
+
+/// Below this node count dispatching to the thread pool costs more than it saves.
+#[cfg(feature = "parallel")]
+const MIN_PARALLEL_NODES: usize = 256;
+
+/// Whether `len` nodes are worth splitting across threads. A single-threaded pool (e.g. on
+/// wasm or inside `ThreadPool::install` with one thread) always takes the sequential path.
+#[cfg(feature = "parallel")]
+pub(crate) fn use_parallel(len: usize) -> bool {
+    len >= MIN_PARALLEL_NODES && rayon::current_num_threads() > 1
+}
+
+/// Calls `f` with every element of `out` and its position. With the `parallel` feature big
+/// slices are split across rayon threads. Every element is written by exactly one call, so
+/// the result does not depend on the thread count.
+pub(crate) fn for_each_node<T, F>(out: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(usize, &mut T) + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if use_parallel(out.len()) {
+        use rayon::prelude::*;
+        out.par_iter_mut().enumerate().for_each(|(i, o)| f(i, o));
+        return;
+    }
+    out.iter_mut().enumerate().for_each(|(i, o)| f(i, o));
+}
+
+/// Locations of `indices`, in order. Graphs are not `Sync`, threads read this copy instead.
+pub(crate) fn positions<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+) -> Vec<Pos2>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    indices
+        .iter()
+        .map(|&idx| g.g().node_weight(idx).unwrap().location())
+        .collect()
+}
@@ -0,0 +1,283 @@
+use egui::{Pos2, Rect, Vec2};
+
+/// Maximum subdivision depth. Bodies which still share a cell at this depth
+/// (e.g. coincident points) are kept together in one leaf and handled exactly.
+const MAX_DEPTH: u32 = 24;
+
+/// Cells pending in [`QuadTree::accumulate`] at most: three siblings on every level down to
+/// the deepest internal cell (at `MAX_DEPTH - 1`), plus the four children of that cell.
+const STACK_LEN: usize = 3 * MAX_DEPTH as usize + 1;
+
+const NONE: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+struct Cell {
+    rect: Rect,
+    /// Total mass of bodies inside the cell.
+    mass: f32,
+    /// Mass-weighted position sum; divided by `mass` after build.
+    com: Vec2,
+    /// Index of the first of four children in `cells`, `NONE` for leaves.
+    children: u32,
+    /// Head of the body list stored in this leaf, `NONE` when empty or internal.
+    body: u32,
+}
+
+impl Cell {
+    fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            mass: 0.0,
+            com: Vec2::ZERO,
+            children: NONE,
+            body: NONE,
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children == NONE
+    }
+}
+
+/// Barnes-Hut quadtree over point masses.
+///
+/// Built once per simulation step from node positions; used to approximate
+/// far-field pairwise forces in O(n log n).
+#[derive(Debug, Default)]
+pub(crate) struct QuadTree {
+    cells: Vec<Cell>,
+    positions: Vec<Pos2>,
+    masses: Vec<f32>,
+    /// Intrusive linked list of bodies sharing a leaf.
+    next: Vec<u32>,
+}
+
+impl QuadTree {
+    /// Rebuilds the tree from `positions` and their `masses` (same length).
+    pub fn build(&mut self, positions: &[Pos2], masses: &[f32]) {
+        debug_assert_eq!(positions.len(), masses.len());
+        self.cells.clear();
+        self.positions.clear();
+        self.positions.extend_from_slice(positions);
+        self.masses.clear();
+        self.masses.extend_from_slice(masses);
+        self.next.clear();
+        self.next.resize(positions.len(), NONE);
+
+        if positions.is_empty() {
+            return;
+        }
+
+        let mut bounds = Rect::NOTHING;
+        for p in positions
+            .iter()
+            .filter(|p| p.x.is_finite() && p.y.is_finite())
+        {
+            bounds.extend_with(*p);
+        }
+        if bounds.min.x > bounds.max.x {
+            return;
+        }
+        // Square root cell so that subdivision keeps cells square.
+        let side = bounds.width().max(bounds.height()).max(1.0);
+        let root = Rect::from_center_size(bounds.center(), Vec2::splat(side * 1.01));
+        self.cells.push(Cell::new(root));
+
+        for (i, p) in positions.iter().enumerate() {
+            if p.x.is_finite() && p.y.is_finite() {
+                self.insert(i as u32);
+            }
+        }
+
+        self.finalize(0);
+    }
+
+    fn insert(&mut self, body: u32) {
+        let p = self.positions[body as usize];
+        let mut cell = 0usize;
+        let mut depth = 0;
+        loop {
+            if self.cells[cell].is_leaf() {
+                let head = self.cells[cell].body;
+                if head == NONE || depth >= MAX_DEPTH || self.positions[head as usize] == p {
+                    self.next[body as usize] = head;
+                    self.cells[cell].body = body;
+                    return;
+                }
+                self.split(cell);
+            }
+            cell = self.child_for(cell, p);
+            depth += 1;
+        }
+    }
+
+    /// Turns a leaf into an internal cell and pushes its bodies one level down.
+    fn split(&mut self, cell: usize) {
+        let rect = self.cells[cell].rect;
+        let c = rect.center();
+        let first = self.cells.len() as u32;
+        self.cells.push(Cell::new(Rect::from_min_max(rect.min, c)));
+        self.cells.push(Cell::new(Rect::from_min_max(
+            Pos2::new(c.x, rect.min.y),
+            Pos2::new(rect.max.x, c.y),
+        )));
+        self.cells.push(Cell::new(Rect::from_min_max(
+            Pos2::new(rect.min.x, c.y),
+            Pos2::new(c.x, rect.max.y),
+        )));
+        self.cells.push(Cell::new(Rect::from_min_max(c, rect.max)));
+
+        let mut b = self.cells[cell].body;
+        self.cells[cell].body = NONE;
+        self.cells[cell].children = first;
+        while b != NONE {
+            let nxt = self.next[b as usize];
+            let child = self.child_for(cell, self.positions[b as usize]);
+            self.next[b as usize] = self.cells[child].body;
+            self.cells[child].body = b;
+            b = nxt;
+        }
+    }
+
+    fn child_for(&self, cell: usize, p: Pos2) -> usize {
+        let c = self.cells[cell].rect.center();
+        let mut q = 0;
+        if p.x >= c.x {
+            q += 1;
+        }
+        if p.y >= c.y {
+            q += 2;
+        }
+        self.cells[cell].children as usize + q
+    }
+
+    /// Computes aggregated mass and center of mass bottom-up.
+    fn finalize(&mut self, cell: usize) {
+        let (mut mass, mut com) = (0.0f32, Vec2::ZERO);
+        if self.cells[cell].is_leaf() {
+            let mut b = self.cells[cell].body;
+            while b != NONE {
+                let m = self.masses[b as usize];
+                mass += m;
+                com += self.positions[b as usize].to_vec2() * m;
+                b = self.next[b as usize];
+            }
+        } else {
+            let first = self.cells[cell].children as usize;
+            for child in first..first + 4 {
+                self.finalize(child);
+                let c = &self.cells[child];
+                mass += c.mass;
+                com += c.com * c.mass;
+            }
+        }
+        self.cells[cell].mass = mass;
+        self.cells[cell].com = if mass > 0.0 { com / mass } else { Vec2::ZERO };
+    }
+
+    /// Sums `force(delta, mass, body)` acting on body `i` from all other bodies, where
+    /// `delta = pos_i - pos_other`. Cells whose size-to-distance ratio is below
+    /// `theta` are treated as a single body at their center of mass and are passed
+    /// with `body == None`; individual bodies are passed with their index.
+    ///
+    /// `theta = 0` degenerates to the exact pairwise sum.
+    pub fn accumulate(
+        &self,
+        i: usize,
+        theta: f32,
+        force: impl Fn(Vec2, f32, Option<usize>) -> Vec2,
+    ) -> Vec2 {
+        let mut acc = Vec2::ZERO;
+        if self.cells.is_empty() {
+            return acc;
+        }
+        let p = self.positions[i];
+        let theta2 = theta * theta;
+        let mut stack = [0u32; STACK_LEN];
+        let mut len = 1;
+        while len > 0 {
+            len -= 1;
+            let c = &self.cells[stack[len] as usize];
+            if c.mass == 0.0 {
+                continue;
+            }
+            if c.is_leaf() {
+                let mut b = c.body;
+                while b != NONE {
+                    if b as usize != i {
+                        acc += force(
+                            p - self.positions[b as usize],
+                            self.masses[b as usize],
+                            Some(b as usize),
+                        );
+                    }
+                    b = self.next[b as usize];
+                }
+                continue;
+            }
+            let delta = p - c.com.to_pos2();
+            let size = c.rect.width();
+            if !c.rect.contains(p) && size * size < theta2 * delta.length_sq() {
+                acc += force(delta, c.mass, None);
+                continue;
+            }
+            let first = c.children;
+            stack[len..len + 4].copy_from_slice(&[first, first + 1, first + 2, first + 3]);
+            len += 4;
+        }
+        acc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn theta_zero_matches_exact_sum() {
+        let positions: Vec<Pos2> = (0..50)
+            .map(|i| Pos2::new((i * 37 % 101) as f32, (i * 53 % 97) as f32))
+            .collect();
+        let masses = vec![1.0; positions.len()];
+        let mut qt = QuadTree::default();
+        qt.build(&positions, &masses);
+
+        let f = |d: Vec2, m: f32, _: Option<usize>| d.normalized() * m / d.length().max(1e-3);
+        for i in 0..positions.len() {
+            let exact = positions
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(Vec2::ZERO, |acc, (_, q)| {
+                    acc + f(positions[i] - *q, 1.0, None)
+                });
+            let approx = qt.accumulate(i, 0.0, f);
+            assert!((exact - approx).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn coincident_points_do_not_recurse_forever() {
+        let positions = vec![Pos2::new(1.0, 1.0); 10];
+        let masses = vec![1.0; positions.len()];
+        let mut qt = QuadTree::default();
+        qt.build(&positions, &masses);
+        let total = qt.accumulate(0, 0.8, |_, m, _| Vec2::new(m, 0.0));
+        assert_eq!(total, Vec2::new(9.0, 0.0));
+    }
+
+    #[test]
+    fn traversal_reaches_max_depth() {
+        // Two points this close share a cell down to `MAX_DEPTH`, so the traversal goes as
+        // deep as the tree can.
+        let mut positions = vec![Pos2::new(0.0, 0.0), Pos2::new(1e-7, 1e-7)];
+        positions.extend((1..40).map(|i| Pos2::new(i as f32 * 7.0, (i * i % 13) as f32)));
+        let masses = vec![1.0; positions.len()];
+        let mut qt = QuadTree::default();
+        qt.build(&positions, &masses);
+        for i in 0..positions.len() {
+            let total = qt.accumulate(i, 0.0, |_, m, _| Vec2::new(m, 0.0));
+            assert_eq!(total, Vec2::new(positions.len() as f32 - 1.0, 0.0));
+        }
+    }
+}
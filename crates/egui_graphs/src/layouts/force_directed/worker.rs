@@ -0,0 +1,435 @@
+use std::{
+    fmt::Debug,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+};
+
+use egui::{Pos2, Rect, Vec2};
+use petgraph::{
+    csr::IndexType,
+    stable_graph::{EdgeIndex, NodeIndex, StableGraph},
+    visit::{EdgeRef, IntoEdgeReferences},
+    EdgeType,
+};
+use web_time::Instant;
+
+use crate::{
+    layouts::{
+        snapshot::{Snapshot, SnapshotGraph},
+        AnimatedState,
+    },
+    node_size, DisplayEdge, DisplayNode, ForceAlgorithm, Graph,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct NodeSnapshot {
+    idx: usize,
+    pos: Pos2,
+    /// Whether the location was ever set, see [`crate::Node::location_set`].
+    placed: bool,
+    /// Pinned or dragged: the worker treats it as an anchor and never moves it.
+    fixed: bool,
+    /// Half extents along the X and Y axes.
+    half: Vec2,
+}
+
+/// Everything the worker needs for one step, taken on the UI thread.
+pub(crate) struct Job<S> {
+    state: S,
+    /// Parameter generation of `state`, see [`super::ForceDirectedBackgroundState::generation`].
+    generation: u64,
+    view: Rect,
+    directed: bool,
+    nodes: Vec<NodeSnapshot>,
+    /// `(edge, source, target)` indices.
+    edges: Vec<(usize, usize, usize)>,
+}
+
+impl<S> Job<S> {
+    pub(crate) fn snapshot<N, E, Ty, Ix, Dn, De>(
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        state: S,
+        generation: u64,
+        view: Rect,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let nodes = g
+            .nodes_iter()
+            .map(|(idx, n)| NodeSnapshot {
+                idx: idx.index(),
+                pos: n.location(),
+                placed: n.location_set(),
+                fixed: n.pinned() || n.dragged(),
+                half: Vec2::new(node_size(n, Vec2::X), node_size(n, Vec2::Y)),
+            })
+            .collect();
+        let edges = g
+            .g()
+            .edge_references()
+            .map(|e| (e.id().index(), e.source().index(), e.target().index()))
+            .collect();
+        Self {
+            state,
+            generation,
+            view,
+            directed: g.is_directed(),
+            nodes,
+            edges,
+        }
+    }
+}
+
+/// Result of one worker step.
+pub(crate) struct Output {
+    /// New locations of all movable nodes by node index.
+    pub positions: Vec<(usize, Pos2)>,
+    pub step_count: u64,
+    pub last_avg_displacement: Option<f32>,
+    pub step_time_ms: f32,
+}
+
+#[derive(Default)]
+struct Slot<S> {
+    job: Option<Job<S>>,
+    output: Option<Output>,
+    busy: bool,
+    shutdown: bool,
+}
+
+struct Shared<S> {
+    slot: Mutex<Slot<S>>,
+    wake: Condvar,
+}
+
+impl<S> Shared<S> {
+    fn lock(&self) -> MutexGuard<'_, Slot<S>> {
+        self.slot.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handle of a thread stepping a [`ForceAlgorithm`]. The thread stops once the handle is dropped.
+pub(crate) struct Worker<S> {
+    shared: Arc<Shared<S>>,
+}
+
+impl<S> Worker<S>
+where
+    S: Clone + AnimatedState + Send + 'static,
+{
+    pub(crate) fn spawn<A: ForceAlgorithm<State = S>>() -> Self {
+        let shared = Arc::new(Shared {
+            slot: Mutex::new(Slot {
+                job: None,
+                output: None,
+                busy: false,
+                shutdown: false,
+            }),
+            wake: Condvar::new(),
+        });
+        let remote = Arc::clone(&shared);
+        thread::Builder::new()
+            .name("egui_graphs_layout".to_string())
+            .spawn(move || run::<A>(&remote))
+            .expect("failed to spawn layout worker thread");
+        Self { shared }
+    }
+
+    /// Whether a submitted job is still being processed.
+    pub(crate) fn is_busy(&self) -> bool {
+        self.shared.lock().busy
+    }
+
+    pub(crate) fn submit(&self, job: Job<S>) {
+        let mut slot = self.shared.lock();
+        slot.job = Some(job);
+        slot.busy = true;
+        self.shared.wake.notify_one();
+    }
+
+    pub(crate) fn take_output(&self) -> Option<Output> {
+        self.shared.lock().output.take()
+    }
+}
+
+impl<S> Drop for Worker<S> {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.wake.notify_one();
+    }
+}
+
+impl<S> Debug for Worker<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Worker").finish_non_exhaustive()
+    }
+}
+
+fn run<A: ForceAlgorithm>(shared: &Shared<A::State>)
+where
+    A::State: AnimatedState,
+{
+    let mut sim = Simulation::default();
+    loop {
+        let job = {
+            let mut slot = shared.lock();
+            loop {
+                if slot.shutdown {
+                    return;
+                }
+                if let Some(job) = slot.job.take() {
+                    break job;
+                }
+                slot = shared
+                    .wake
+                    .wait(slot)
+                    .unwrap_or_else(PoisonError::into_inner);
+            }
+        };
+
+        let output = sim.step::<A>(job);
+
+        let mut slot = shared.lock();
+        slot.output = Some(output);
+        slot.busy = false;
+    }
+}
+
+/// Worker side of the simulation. Keeps the algorithm state between steps, including the
+/// parts the UI never sees (e.g. adaptive speed), and the snapshot graph between frames.
+struct Simulation<S> {
+    state: Option<S>,
+    /// Parameter generation of the last state received from the UI.
+    seen: Option<u64>,
+    graph: Option<Snapshot>,
+    node_ids: Vec<usize>,
+    edges: Vec<(usize, usize, usize)>,
+}
+
+impl<S> Default for Simulation<S> {
+    fn default() -> Self {
+        Self {
+            state: None,
+            seen: None,
+            graph: None,
+            node_ids: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<S: Clone + AnimatedState> Simulation<S> {
+    fn step<A: ForceAlgorithm<State = S>>(&mut self, job: Job<S>) -> Output {
+        let t0 = Instant::now();
+
+        // Keep stepping our own state unless the UI changed parameters since the last job.
+        let state = match self.state.take() {
+            Some(state) if self.seen == Some(job.generation) => state,
+            _ => job.state,
+        };
+        self.seen = Some(job.generation);
+
+        let node_ids: Vec<usize> = job.nodes.iter().map(|n| n.idx).collect();
+        let directed = matches!(self.graph, Some(Snapshot::Directed(_)));
+        if self.graph.is_none()
+            || directed != job.directed
+            || node_ids != self.node_ids
+            || job.edges != self.edges
+        {
+            self.graph = Some(if job.directed {
+                Snapshot::Directed(build(&job.nodes, &job.edges))
+            } else {
+                Snapshot::Undirected(build(&job.nodes, &job.edges))
+            });
+            self.node_ids = node_ids;
+            self.edges = job.edges;
+        }
+
+        let mut alg = A::from_state(state);
+        let positions = match self.graph.as_mut().unwrap() {
+            Snapshot::Directed(g) => step_snapshot(g, &mut alg, &job.nodes, job.view),
+            Snapshot::Undirected(g) => step_snapshot(g, &mut alg, &job.nodes, job.view),
+        };
+        let state = alg.state();
+
+        let output = Output {
+            positions,
+            step_count: state.step_count(),
+            last_avg_displacement: state.last_avg_displacement(),
+            step_time_ms: t0.elapsed().as_secs_f32() * 1000.0,
+        };
+        self.state = Some(state);
+        output
+    }
+}
+
+/// Builds the snapshot graph with the node and edge indices of the source graph, so
+/// index-keyed data in the algorithm state stays valid.
+fn build<Ty: EdgeType>(
+    nodes: &[NodeSnapshot],
+    edges: &[(usize, usize, usize)],
+) -> SnapshotGraph<Ty> {
+    let mut g = Graph::new(StableGraph::default());
+    let node_slots = nodes.iter().map(|n| n.idx + 1).max().unwrap_or(0);
+    for _ in 0..node_slots {
+        g.add_node(());
+    }
+
+    let mut edge_slots = vec![None; edges.iter().map(|e| e.0 + 1).max().unwrap_or(0)];
+    for &(e, source, target) in edges {
+        edge_slots[e] = Some((source, target));
+    }
+    for slot in &edge_slots {
+        let (source, target) = slot.unwrap_or((0, 0));
+        g.add_edge(NodeIndex::new(source), NodeIndex::new(target), ());
+    }
+
+    // Drop the placeholders filling index holes of the source graph.
+    for (e, slot) in edge_slots.iter().enumerate() {
+        if slot.is_none() {
+            g.g_mut().remove_edge(EdgeIndex::new(e));
+        }
+    }
+    let mut present = vec![false; node_slots];
+    for n in nodes {
+        present[n.idx] = true;
+    }
+    for (i, present) in present.into_iter().enumerate() {
+        if !present {
+            g.g_mut().remove_node(NodeIndex::new(i));
+        }
+    }
+    g
+}
+
+fn step_snapshot<Ty: EdgeType, A: ForceAlgorithm>(
+    g: &mut SnapshotGraph<Ty>,
+    alg: &mut A,
+    nodes: &[NodeSnapshot],
+    view: Rect,
+) -> Vec<(usize, Pos2)> {
+    for n in nodes {
+        let node = &mut g.g_mut()[NodeIndex::new(n.idx)];
+        if n.placed {
+            node.set_location(n.pos);
+        }
+        node.set_pinned(n.fixed);
+        node.display_mut().half = n.half;
+    }
+
+    alg.step(g, view);
+
+    nodes
+        .iter()
+        .filter(|n| !n.fixed)
+        .map(|n| (n.idx, g.g()[NodeIndex::new(n.idx)].location()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DefaultEdgeShape, DefaultNodeShape, FruchtermanReingold, FruchtermanReingoldState,
+    };
+    use petgraph::Directed;
+    use std::time::Duration;
+
+    type G = Graph<(), (), Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    fn graph() -> G {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let ns: Vec<_> = (0..6).map(|_| sg.add_node(())).collect();
+        for (a, b) in [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 0), (0, 3)] {
+            sg.add_edge(ns[a], ns[b], ());
+        }
+        let mut g = G::from(&sg);
+        for (i, idx) in ns.iter().enumerate() {
+            let loc = Pos2::new((i * 37 % 11) as f32 * 20.0, (i * 13 % 7) as f32 * 20.0);
+            g.node_mut(*idx).unwrap().set_location(loc);
+        }
+        // Leave index holes behind to check that indices are kept.
+        g.remove_node(ns[2]);
+        g
+    }
+
+    fn view() -> Rect {
+        Rect::from_min_max(Pos2::ZERO, Pos2::new(800.0, 600.0))
+    }
+
+    #[test]
+    fn snapshot_step_matches_synchronous_step() {
+        let mut expected = graph();
+        let mut alg = FruchtermanReingold::from_state(FruchtermanReingoldState::default());
+        alg.step(&mut expected, view());
+        alg.step(&mut expected, view());
+
+        let mut sim = Simulation::default();
+        let mut g = graph();
+        for _ in 0..2 {
+            let job = Job::snapshot(&g, FruchtermanReingoldState::default(), 0, view());
+            let out = sim.step::<FruchtermanReingold>(job);
+            for (idx, pos) in out.positions {
+                g.node_mut(NodeIndex::new(idx)).unwrap().set_location(pos);
+            }
+        }
+
+        for (idx, n) in expected.nodes_iter() {
+            assert!((g.node(idx).unwrap().location() - n.location()).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn worker_skips_pinned_nodes() {
+        let mut g = graph();
+        let pinned = NodeIndex::new(0);
+        g.node_mut(pinned).unwrap().set_pinned(true);
+
+        let worker = Worker::spawn::<FruchtermanReingold>();
+        worker.submit(Job::snapshot(
+            &g,
+            FruchtermanReingoldState::default(),
+            0,
+            view(),
+        ));
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let out = loop {
+            if let Some(out) = worker.take_output() {
+                break out;
+            }
+            assert!(Instant::now() < deadline, "worker did not finish a step");
+            thread::sleep(Duration::from_millis(1));
+        };
+
+        assert!(!worker.is_busy());
+        assert_eq!(out.step_count, 1);
+        assert_eq!(out.positions.len(), g.node_count() - 1);
+        assert!(out.positions.iter().all(|&(idx, _)| idx != pinned.index()));
+    }
+
+    #[test]
+    fn picks_up_parameters_when_generation_changes() {
+        let g = graph();
+        let mut sim = Simulation::default();
+        let changed = FruchtermanReingoldState {
+            k_scale: 3.0,
+            ..Default::default()
+        };
+        let k_scale =
+            |sim: &Simulation<FruchtermanReingoldState>| sim.state.as_ref().unwrap().k_scale;
+
+        let job = Job::snapshot(&g, FruchtermanReingoldState::default(), 0, view());
+        sim.step::<FruchtermanReingold>(job);
+        sim.step::<FruchtermanReingold>(Job::snapshot(&g, changed.clone(), 0, view()));
+        assert!((k_scale(&sim) - FruchtermanReingoldState::default().k_scale).abs() < f32::EPSILON);
+
+        sim.step::<FruchtermanReingold>(Job::snapshot(&g, changed, 1, view()));
+        assert!((k_scale(&sim) - 3.0).abs() < f32::EPSILON);
+    }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use egui::Pos2;
+use egui::{Pos2, Vec2};
 use petgraph::{
     csr::IndexType,
     stable_graph::NodeIndex,
@@ -11,7 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     layouts::{Layout, LayoutState},
-    DisplayEdge, DisplayNode, Graph,
+    node_size, DisplayEdge, DisplayNode, Graph,
 };
 
 /// Orientation of the hierarchical layout.
@@ -22,17 +22,51 @@ pub enum Orientation {
     TopDown,
     /// Levels grow to the right. Rows are horizontal steps.
     LeftRight,
+    /// Levels grow upward. Rows are vertical steps.
+    BottomUp,
+    /// Levels grow to the left. Rows are horizontal steps.
+    RightLeft,
+}
+
+impl Orientation {
+    /// Canvas position of a node placed at `col` along its level and `row` across levels.
+    pub(crate) fn place(self, col: f32, row: f32) -> Pos2 {
+        match self {
+            Orientation::TopDown => Pos2::new(col, row),
+            Orientation::LeftRight => Pos2::new(row, col),
+            Orientation::BottomUp => Pos2::new(col, -row),
+            Orientation::RightLeft => Pos2::new(-row, col),
+        }
+    }
+
+    /// Unit direction along which the nodes of one level are spread.
+    pub(crate) fn level_axis(self) -> Vec2 {
+        match self {
+            Orientation::TopDown | Orientation::BottomUp => Vec2::X,
+            Orientation::LeftRight | Orientation::RightLeft => Vec2::Y,
+        }
+    }
+
+    /// Unit direction in which levels grow, i.e. the way edges point from parent to child.
+    pub(crate) fn flow_axis(self) -> Vec2 {
+        self.place(0.0, 1.0).to_vec2()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
     /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
     pub triggered: bool,
-    /// Distance between levels (rows). Interpreted as Y step for TopDown and X step for LeftRight.
+    /// Gap between the borders of nodes on adjacent levels (rows), node sizes come on top.
+    /// Earlier versions used it as the distance between node centers.
+    /// Interpreted as Y step for TopDown/BottomUp and X step for LeftRight/RightLeft.
     pub row_dist: f32,
-    /// Distance between siblings/columns. Interpreted as X step for TopDown and Y step for LeftRight.
+    /// Gap between the borders of neighbouring subtrees (columns), node sizes come on top.
+    /// Earlier versions used it as the distance between node centers.
+    /// Interpreted as X step for TopDown/BottomUp and Y step for LeftRight/RightLeft.
     pub col_dist: f32,
     /// Center a parent above/beside the span of its children.
+    /// When disabled the parent is aligned with its first child.
     pub center_parent: bool,
     /// Layout orientation.
     pub orientation: Orientation,
@@ -50,7 +84,11 @@ impl Default for State {
     }
 }
 
-impl LayoutState for State {}
+impl LayoutState for State {
+    fn is_done(&self) -> bool {
+        self.triggered
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct Hierarchical {
@@ -71,53 +109,193 @@ impl Layout<State> for Hierarchical {
             return;
         }
 
-        let mut visited = HashSet::new();
+        let forest = Forest::build(g);
+        layout_forest(g, &forest, &self.state);
 
-        // Place forests starting from all roots (no incoming edges), packing them left-to-right
-        // without overlap by advancing the next starting column by the width of each subtree.
-        let mut next_col: usize = 0;
-        let roots: Vec<NodeIndex<Ix>> = g.g().externals(Incoming).collect();
-        for root in &roots {
-            if visited.contains(root) {
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Hierarchical { state }
+    }
+}
+
+/// Depth-first spanning forest of the graph. Local ids are assigned in pre-order,
+/// so every parent id is smaller than the ids of its children.
+pub(crate) struct Forest<Ix: IndexType> {
+    pub nodes: Vec<NodeIndex<Ix>>,
+    pub children: Vec<Vec<usize>>,
+    pub parent: Vec<Option<usize>>,
+    pub depth: Vec<usize>,
+}
+
+impl<Ix: IndexType> Forest<Ix> {
+    /// Trees are grown from all roots (no incoming edges) first. Nodes left over
+    /// because of cycles or components without formal roots start trees of their own.
+    pub fn build<N, E, Ty, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let starts: Vec<NodeIndex<Ix>> = g
+            .g()
+            .externals(Incoming)
+            .chain(g.g().node_indices())
+            .collect();
+        Self::build_from(g, starts)
+    }
+
+    /// Grows trees from `starts` in order; starts already reached by an earlier tree are
+    /// skipped. Every node of the graph must appear in `starts`.
+    pub fn build_from<N, E, Ty, Dn, De>(
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        starts: Vec<NodeIndex<Ix>>,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut f = Self {
+            nodes: Vec::new(),
+            children: Vec::new(),
+            parent: Vec::new(),
+            depth: Vec::new(),
+        };
+        let mut visited = HashSet::new();
+        for start in starts {
+            if !visited.insert(start) {
                 continue;
             }
+            let root = f.push(start, None);
+            let mut stack = vec![(
+                root,
+                g.g()
+                    .neighbors_directed(start, Outgoing)
+                    .collect::<Vec<_>>(),
+                0,
+            )];
+            while let Some(top) = stack.last_mut() {
+                let Some(&child) = top.1.get(top.2) else {
+                    stack.pop();
+                    continue;
+                };
+                top.2 += 1;
+                let parent = top.0;
+                if !visited.insert(child) {
+                    continue;
+                }
+                let id = f.push(child, Some(parent));
+                stack.push((id, g.g().neighbors_directed(child, Outgoing).collect(), 0));
+            }
+        }
+        f
+    }
 
-            let curr_max_col = layout_tree(g, &mut visited, root, &self.state, 0, next_col);
-            next_col = curr_max_col + 1;
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn push(&mut self, idx: NodeIndex<Ix>, parent: Option<usize>) -> usize {
+        let id = self.nodes.len();
+        self.nodes.push(idx);
+        self.children.push(Vec::new());
+        self.parent.push(parent);
+        self.depth.push(parent.map_or(0, |p| self.depth[p] + 1));
+        if let Some(p) = parent {
+            self.children[p].push(id);
         }
+        id
+    }
+}
 
-        // Fallback: if the graph has cycles or no formal roots, lay out any remaining components.
-        let all_nodes: Vec<NodeIndex<Ix>> = g.g().node_indices().collect();
-        for n in &all_nodes {
-            if visited.contains(n) {
-                continue;
-            }
+/// Left and right outline of a subtree per depth, relative to the subtree root.
+///
+/// Levels are stored deepest first so that a parent level can be pushed in O(1), and
+/// the whole outline is moved by adjusting `shift` instead of touching every level.
+struct Contour {
+    shift: f32,
+    levels: Vec<(f32, f32)>,
+}
 
-            let curr_max_col = layout_tree(g, &mut visited, n, &self.state, 0, next_col);
-            next_col = curr_max_col + 1;
+impl Contour {
+    fn leaf(half: f32) -> Self {
+        Self {
+            shift: 0.0,
+            levels: vec![(-half, half)],
         }
+    }
 
-        self.state.triggered = true;
+    fn depth(&self) -> usize {
+        self.levels.len()
     }
 
-    fn state(&self) -> State {
-        self.state.clone()
+    fn at(&self, d: usize) -> (f32, f32) {
+        let (l, r) = self.levels[self.levels.len() - 1 - d];
+        (l + self.shift, r + self.shift)
     }
 
-    fn from_state(state: State) -> impl Layout<State> {
-        Hierarchical { state }
+    fn set(&mut self, d: usize, (l, r): (f32, f32)) {
+        let i = self.levels.len() - 1 - d;
+        self.levels[i] = (l - self.shift, r - self.shift);
+    }
+
+    fn push_top(&mut self, (l, r): (f32, f32)) {
+        self.levels.push((l - self.shift, r - self.shift));
+    }
+
+    /// Smallest offset for `right` so that it keeps `gap` from `self` on every shared level.
+    fn separation(&self, right: &Contour, gap: f32) -> f32 {
+        (0..self.depth().min(right.depth()))
+            .map(|d| self.at(d).1 - right.at(d).0 + gap)
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// Outline of `self` and `right` placed next to each other. Only the levels both
+    /// share are visited, the deeper outline is reused as is.
+    fn merge(mut self, mut right: Contour) -> Contour {
+        let common = self.depth().min(right.depth());
+        if self.depth() >= right.depth() {
+            for d in 0..common {
+                let (l, _) = self.at(d);
+                self.set(d, (l, right.at(d).1));
+            }
+            self
+        } else {
+            for d in 0..common {
+                let (_, r) = right.at(d);
+                right.set(d, (self.at(d).0, r));
+            }
+            right
+        }
+    }
+
+    fn bounds(&self) -> (f32, f32) {
+        (0..self.depth())
+            .map(|d| self.at(d))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), (l, r)| {
+                (lo.min(l), hi.max(r))
+            })
     }
 }
 
-fn layout_tree<N, E, Ty, Ix, Dn, De>(
+/// Reingold-Tilford style placement: subtrees are pushed together as closely as their
+/// outlines allow, parents are centered over or aligned with their children and levels
+/// are spaced by the largest node on them.
+fn layout_forest<N, E, Ty, Ix, Dn, De>(
     g: &mut Graph<N, E, Ty, Ix, Dn, De>,
-    visited: &mut HashSet<NodeIndex<Ix>>,
-    root_idx: &NodeIndex<Ix>,
+    forest: &Forest<Ix>,
     state: &State,
-    start_row: usize,
-    start_col: usize,
-) -> usize
-where
+) where
     N: Clone,
     E: Clone,
     Ty: EdgeType,
@@ -125,46 +303,164 @@ where
     Dn: DisplayNode<N, E, Ty, Ix>,
     De: DisplayEdge<N, E, Ty, Ix, Dn>,
 {
-    // Mark current node as visited to avoid re-entrancy via back-edges/cycles.
-    if !visited.contains(root_idx) {
-        visited.insert(*root_idx);
+    let n = forest.len();
+    let along = state.orientation.level_axis();
+    let (width, height): (Vec<f32>, Vec<f32>) = forest
+        .nodes
+        .iter()
+        .map(|&idx| {
+            let node = &g.g()[idx];
+            (
+                2.0 * node_size(node, along),
+                2.0 * node_size(node, along.yx()),
+            )
+        })
+        .unzip();
+
+    // Children have larger ids than their parents, so a reverse sweep is a post-order.
+    let mut contours: Vec<Option<Contour>> = (0..n).map(|_| None).collect();
+    let mut offset = vec![0.0f32; n];
+    for v in (0..n).rev() {
+        let half = width[v] / 2.0;
+        let kids = &forest.children[v];
+        let (Some(&first), Some(&last)) = (kids.first(), kids.last()) else {
+            contours[v] = Some(Contour::leaf(half));
+            continue;
+        };
+
+        let mut merged = contours[first].take().unwrap();
+        for &c in &kids[1..] {
+            let mut next = contours[c].take().unwrap();
+            let shift = merged.separation(&next, state.col_dist);
+            next.shift += shift;
+            offset[c] = shift;
+            merged = merged.merge(next);
+        }
+
+        let anchor = if state.center_parent {
+            (offset[first] + offset[last]) / 2.0
+        } else {
+            offset[first]
+        };
+        for &c in kids {
+            offset[c] -= anchor;
+        }
+        merged.shift -= anchor;
+        merged.push_top((-half, half));
+        contours[v] = Some(merged);
     }
 
-    let children: Vec<NodeIndex<Ix>> = g.g().neighbors_directed(*root_idx, Outgoing).collect();
+    // Trees of the forest are packed side by side without overlap.
+    let mut cols = vec![0.0f32; n];
+    let mut cursor: Option<f32> = None;
+    for v in 0..n {
+        match forest.parent[v] {
+            Some(p) => cols[v] = cols[p] + offset[v],
+            None => {
+                let (lo, hi) = contours[v].as_ref().unwrap().bounds();
+                cols[v] = cursor.map_or(0.0, |c| c + state.col_dist - lo);
+                cursor = Some(cols[v] + hi);
+            }
+        }
+    }
 
-    // Traverse children to compute the horizontal span of this subtree.
-    let mut max_col = start_col;
-    let mut child_col = start_col;
-    for neighbour_idx in children.iter() {
-        if visited.contains(neighbour_idx) {
-            continue;
+    let depth = forest.depth.iter().copied().max().map_or(0, |d| d + 1);
+    let mut half_heights = vec![0.0f32; depth];
+    for v in 0..n {
+        let h = &mut half_heights[forest.depth[v]];
+        *h = h.max(height[v] / 2.0);
+    }
+    let mut rows = Vec::with_capacity(depth);
+    let mut row = 0.0;
+    for (d, half) in half_heights.iter().enumerate() {
+        if d > 0 {
+            row += half_heights[d - 1] + state.row_dist + half;
         }
-        visited.insert(*neighbour_idx);
+        rows.push(row);
+    }
 
-        let child_max_col = layout_tree(g, visited, neighbour_idx, state, start_row + 1, child_col);
-        if child_max_col > max_col {
-            max_col = child_max_col;
+    for v in 0..n {
+        let node = &mut g.g_mut()[forest.nodes[v]];
+        if !node.pinned() {
+            node.set_location(state.orientation.place(cols[v], rows[forest.depth[v]]));
         }
-        child_col = child_max_col.saturating_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    fn run(g: &mut G, state: &State) {
+        let forest = Forest::build(g);
+        layout_forest(g, &forest, state);
+    }
+
+    /// Root with two children, the first of which has two children of its own.
+    fn sample() -> (G, Vec<NodeIndex>) {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let ns: Vec<_> = (0..5).map(|_| sg.add_node(())).collect();
+        sg.add_edge(ns[0], ns[1], ());
+        sg.add_edge(ns[0], ns[2], ());
+        sg.add_edge(ns[1], ns[3], ());
+        sg.add_edge(ns[1], ns[4], ());
+        (Graph::from(&sg), ns)
+    }
+
+    #[test]
+    fn center_parent_centers_over_children() {
+        let (mut g, ns) = sample();
+        let state = State {
+            center_parent: true,
+            ..State::default()
+        };
+        run(&mut g, &state);
+
+        let x = |i: usize| g.g()[ns[i]].location().x;
+        assert!((x(1) - (x(3) + x(4)) / 2.0).abs() < 1e-3);
+        assert!((x(0) - (x(1) + x(2)) / 2.0).abs() < 1e-3);
     }
 
-    // Column where the current node will be placed.
-    let place_col = start_col;
+    #[test]
+    fn spacing_accounts_for_node_size() {
+        let (mut g, ns) = sample();
+        for &n in &ns {
+            g.g_mut()[n].display_mut().radius = 40.0;
+        }
+        run(&mut g, &State::default());
 
-    // Compute actual coordinates based on orientation.
-    let (x, y) = match state.orientation {
-        Orientation::TopDown => (
-            (place_col as f32) * state.col_dist,
-            (start_row as f32) * state.row_dist,
-        ),
-        Orientation::LeftRight => (
-            (start_row as f32) * state.row_dist,
-            (place_col as f32) * state.col_dist,
-        ),
-    };
+        let loc = |i: usize| g.g()[ns[i]].location();
+        assert!((loc(4).x - loc(3).x).abs() >= 80.0 + 50.0 - 1e-3);
+        assert!((loc(2).x - loc(1).x).abs() >= 80.0 + 50.0 - 1e-3);
+        assert!(loc(1).y - loc(0).y >= 80.0 + 50.0 - 1e-3);
+        // Without centering the parent is aligned with its first child.
+        assert!((loc(0).x - loc(1).x.min(loc(2).x)).abs() < 1e-3);
+    }
 
-    let node = &mut g.g_mut()[*root_idx];
-    node.set_location(Pos2::new(x, y));
+    #[test]
+    fn reversed_orientations_mirror_rows() {
+        let (mut g, ns) = sample();
+        run(&mut g, &State::default());
+        let down: Vec<Pos2> = ns.iter().map(|&n| g.g()[n].location()).collect();
 
-    max_col
+        type Mirror = fn(Pos2) -> Pos2;
+        let mirrored: [(Orientation, Mirror); 2] = [
+            (Orientation::BottomUp, |p| Pos2::new(p.x, -p.y)),
+            (Orientation::RightLeft, |p| Pos2::new(-p.y, p.x)),
+        ];
+        for (orientation, expected) in mirrored {
+            let state = State {
+                orientation,
+                ..State::default()
+            };
+            run(&mut g, &state);
+            for (i, &n) in ns.iter().enumerate() {
+                assert!((g.g()[n].location() - expected(down[i])).length() < 1e-3);
+            }
+        }
+    }
 }
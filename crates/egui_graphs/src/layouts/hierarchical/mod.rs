@@ -1,3 +1,4 @@
 mod layout;
 
+pub(crate) use layout::Forest;
 pub use layout::{Hierarchical, Orientation, State};
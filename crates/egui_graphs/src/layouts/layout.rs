@@ -20,6 +20,17 @@ pub trait LayoutState: SerializableAny + Default + Debug {
             data.insert_persisted(get_id(id), self);
         });
     }
+
+    /// Whether the layout has nothing left to do, e.g. a one-shot layout that was applied.
+    /// Default: never done.
+    fn is_done(&self) -> bool {
+        false
+    }
+
+    /// The state as [`AnimatedState`] if the layout is simulated. Default: `None`.
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        None
+    }
 }
 
 /// Optional hooks for animated/simulated layout states.
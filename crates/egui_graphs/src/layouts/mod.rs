@@ -1,6 +1,14 @@
+pub mod circular;
 pub mod force_directed;
 pub mod hierarchical;
+pub mod packing;
+pub mod radial;
 pub mod random;
+pub mod sequence;
+pub mod stress;
+pub mod sugiyama;
+pub mod transition;
 
 mod layout;
+mod snapshot;
 pub use layout::{AnimatedState, Layout, LayoutState};
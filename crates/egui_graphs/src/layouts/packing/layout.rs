@@ -0,0 +1,584 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use egui::{Rect, Vec2};
+use petgraph::{
+    stable_graph::{IndexType, NodeIndex, StableGraph},
+    unionfind::UnionFind,
+    visit::{EdgeRef, IntoEdgeReferences, NodeIndexable},
+    EdgeType,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    layouts::{
+        snapshot::{Snapshot, SnapshotGraph},
+        AnimatedState, Layout, LayoutState,
+    },
+    node_size, DisplayEdge, DisplayNode, Graph,
+};
+
+/// State of [`Packing`]: the wrapped layout state plus the packing settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "S: Serialize", deserialize = "S: DeserializeOwned"))]
+pub struct State<S> {
+    /// State of the wrapped layout. Each new component starts from a copy of it.
+    pub inner: S,
+    /// Wrapped layout state of every component, keyed by the smallest node index in it.
+    /// Clear it to restart all components from `inner`, e.g. after changing its settings.
+    #[serde(default)]
+    pub components: HashMap<usize, S>,
+    /// Gap between the bounding boxes of packed components.
+    pub spacing: f32,
+    /// Width to height ratio of the packed rectangle. `None` uses the `GraphView` rect.
+    pub aspect_ratio: Option<f32>,
+    #[serde(skip)]
+    partition: Option<Arc<Mutex<Partition>>>,
+}
+
+impl<S: Default> Default for State<S> {
+    fn default() -> Self {
+        Self {
+            inner: S::default(),
+            components: HashMap::new(),
+            spacing: 30.0,
+            aspect_ratio: None,
+            partition: None,
+        }
+    }
+}
+
+impl<S: LayoutState + Clone> LayoutState for State<S> {
+    fn is_done(&self) -> bool {
+        if self.components.is_empty() {
+            return self.inner.is_done();
+        }
+        self.components.values().all(LayoutState::is_done)
+    }
+    /// The component that moved the most in the last step, so a settled result means
+    /// every component settled.
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        self.components
+            .values()
+            .filter_map(LayoutState::as_animated)
+            .max_by(|a, b| {
+                let d = |s: &dyn AnimatedState| s.last_avg_displacement().unwrap_or(f32::INFINITY);
+                d(*a).total_cmp(&d(*b))
+            })
+            .or_else(|| self.inner.as_animated())
+    }
+}
+
+impl<S: AnimatedState> AnimatedState for State<S> {
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+    fn set_running(&mut self, v: bool) {
+        self.inner.set_running(v);
+        for s in self.components.values_mut() {
+            s.set_running(v);
+        }
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        if self.components.is_empty() {
+            return self.inner.last_avg_displacement();
+        }
+        self.components
+            .values()
+            .map(AnimatedState::last_avg_displacement)
+            .reduce(|a, b| Some(a?.max(b?)))
+            .flatten()
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.inner.set_last_avg_displacement(v);
+        for s in self.components.values_mut() {
+            s.set_last_avg_displacement(v);
+        }
+    }
+    fn step_count(&self) -> u64 {
+        self.components
+            .values()
+            .map(AnimatedState::step_count)
+            .max()
+            .unwrap_or_else(|| self.inner.step_count())
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.inner.set_step_count(v);
+        for s in self.components.values_mut() {
+            s.set_step_count(v);
+        }
+    }
+}
+
+/// Lays out every connected component on its own with the wrapped layout, then moves
+/// the components as a whole so their bounding boxes are packed into a compact rectangle
+/// with the target aspect ratio.
+///
+/// Each component runs on a sub-graph holding only its structure, node locations and node
+/// sizes, with its own wrapped layout state, so forces of one component never reach
+/// another. Sub-graphs are kept between frames and rebuilt only after nodes or edges are
+/// added or removed; every frame they take the current locations and pins, and only
+/// locations are written back. Node and edge indices inside a sub-graph are the
+/// component's own, settings keyed by index (like [`crate::LayoutStateCircle`] keys) do
+/// not carry over, and layouts reading payloads see `()`. A connected graph is laid out in
+/// place. When components merge the merged one keeps the state of the component holding
+/// the smallest node index; a part split off starts from `inner`.
+///
+/// Components are placed on shelves, tallest first, starting at the top-left corner of
+/// the previous bounding box of the whole graph. Components holding a pinned node stay
+/// where they are, and nothing is moved while a node is dragged.
+#[derive(Debug)]
+pub struct Packing<S, L> {
+    state: State<S>,
+    _layout: PhantomData<fn() -> L>,
+}
+
+impl<S: Default, L> Default for Packing<S, L> {
+    fn default() -> Self {
+        Self {
+            state: State::default(),
+            _layout: PhantomData,
+        }
+    }
+}
+
+impl<S, L> Layout<State<S>> for Packing<S, L>
+where
+    S: LayoutState + Clone,
+    L: Layout<S>,
+{
+    fn from_state(state: State<S>) -> impl Layout<State<S>> {
+        Self {
+            state,
+            _layout: PhantomData,
+        }
+    }
+
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, ui: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let partition = Arc::clone(self.state.partition.get_or_insert_with(Arc::default));
+        let mut partition = partition.lock().unwrap_or_else(PoisonError::into_inner);
+        if partition.structure != Some(g.structure()) {
+            *partition = Partition::new(g);
+        }
+        self.layout_components(g, &mut partition, ui);
+        // A connected graph is laid out in place, through `Graph::g_mut` for some layouts,
+        // which never changes the structure.
+        partition.structure = Some(g.structure());
+
+        if g.nodes_iter().any(|(_, n)| n.dragged()) {
+            return;
+        }
+        let aspect_ratio = self.state.aspect_ratio.unwrap_or_else(|| {
+            let rect = ui.available_rect_before_wrap();
+            rect.width() / rect.height()
+        });
+        pack_components(g, &partition.components, self.state.spacing, aspect_ratio);
+    }
+
+    fn state(&self) -> State<S> {
+        self.state.clone()
+    }
+}
+
+impl<S, L> Packing<S, L>
+where
+    S: LayoutState + Clone,
+    L: Layout<S>,
+{
+    /// Runs the wrapped layout once per component with that component's state.
+    fn layout_components<N, E, Ty, Ix, Dn, De>(
+        &mut self,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+        partition: &mut Partition,
+        ui: &egui::Ui,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let mut prev = std::mem::take(&mut self.state.components);
+        for (c, nodes) in partition.components.iter().enumerate() {
+            let key = nodes[0];
+            let state = prev
+                .remove(&key)
+                .unwrap_or_else(|| self.state.inner.clone());
+            let mut layout = L::from_state(state);
+            match partition.subgraphs.get_mut(c) {
+                None => layout.next(g, ui),
+                Some(Snapshot::Directed(sub)) => step_subgraph(&mut layout, sub, g, nodes, ui),
+                Some(Snapshot::Undirected(sub)) => step_subgraph(&mut layout, sub, g, nodes, ui),
+            }
+            self.state.components.insert(key, layout.state());
+        }
+    }
+}
+
+/// Connected components of a graph with their sub-graphs, kept between frames.
+#[derive(Debug, Default)]
+struct Partition {
+    /// [`Graph::structure`] of the graph the partition was built for.
+    structure: Option<u64>,
+    /// Node indices of every component in increasing order.
+    components: Vec<Vec<usize>>,
+    /// Sub-graph of every component, empty for a connected graph. The node at
+    /// `components[c][i]` has index `i` in `subgraphs[c]`.
+    subgraphs: Vec<Snapshot>,
+}
+
+impl Partition {
+    fn new<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let components = components(g);
+        let subgraphs = if components.len() < 2 {
+            Vec::new()
+        } else if g.is_directed() {
+            subgraphs(g, &components)
+                .into_iter()
+                .map(Snapshot::Directed)
+                .collect()
+        } else {
+            subgraphs(g, &components)
+                .into_iter()
+                .map(Snapshot::Undirected)
+                .collect()
+        };
+        Self {
+            structure: Some(g.structure()),
+            components,
+            subgraphs,
+        }
+    }
+}
+
+/// Node indices of every connected component, each in increasing order, ordered by their
+/// smallest node index.
+fn components<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> Vec<Vec<usize>>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut sets = UnionFind::new(g.g().node_bound());
+    for e in g.g().edge_references() {
+        sets.union(e.source().index(), e.target().index());
+    }
+
+    let mut by_root: HashMap<usize, usize> = HashMap::new();
+    let mut comps: Vec<Vec<usize>> = Vec::new();
+    for idx in g.g().node_indices() {
+        let next = comps.len();
+        let c = *by_root.entry(sets.find_mut(idx.index())).or_insert(next);
+        if c == next {
+            comps.push(Vec::new());
+        }
+        comps[c].push(idx.index());
+    }
+    comps
+}
+
+/// Builds a payload-free sub-graph with the nodes and edges of every component.
+fn subgraphs<SubTy, N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    components: &[Vec<usize>],
+) -> Vec<SnapshotGraph<SubTy>>
+where
+    SubTy: EdgeType,
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    // Component and index inside its sub-graph of every node.
+    let mut slots = vec![(0, 0); g.g().node_bound()];
+    let mut subs: Vec<SnapshotGraph<SubTy>> = Vec::with_capacity(components.len());
+    for (c, nodes) in components.iter().enumerate() {
+        let mut sub = Graph::new(StableGraph::with_capacity(nodes.len(), nodes.len()));
+        for (i, &idx) in nodes.iter().enumerate() {
+            slots[idx] = (c, i);
+            sub.add_node(());
+        }
+        subs.push(sub);
+    }
+    for e in g.g().edge_references() {
+        let (c, source) = slots[e.source().index()];
+        let (_, target) = slots[e.target().index()];
+        subs[c].add_edge(NodeIndex::new(source), NodeIndex::new(target), ());
+    }
+    subs
+}
+
+/// Runs `layout` on the sub-graph of one component after handing it the current
+/// locations, pins and sizes of the component's nodes, then writes moved locations back.
+/// Dragged nodes are pinned in the sub-graph.
+fn step_subgraph<S, SubTy, N, E, Ty, Ix, Dn, De>(
+    layout: &mut impl Layout<S>,
+    sub: &mut SnapshotGraph<SubTy>,
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    nodes: &[usize],
+    ui: &egui::Ui,
+) where
+    S: LayoutState,
+    SubTy: EdgeType,
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    for (i, &idx) in nodes.iter().enumerate() {
+        let node = &g.g()[NodeIndex::new(idx)];
+        let sub_node = sub.node_mut(NodeIndex::new(i)).unwrap();
+        if node.location_set() {
+            sub_node.set_location(node.location());
+        }
+        sub_node.set_pinned(node.pinned() || node.dragged());
+        sub_node.display_mut().half = Vec2::new(node_size(node, Vec2::X), node_size(node, Vec2::Y));
+    }
+
+    layout.next(sub, ui);
+
+    for (i, &idx) in nodes.iter().enumerate() {
+        let sub_node = &sub.g()[NodeIndex::new(i)];
+        let idx = NodeIndex::new(idx);
+        if sub_node.location_set() && g.g()[idx].location() != sub_node.location() {
+            g.node_mut(idx).unwrap().set_location(sub_node.location());
+        }
+    }
+}
+
+/// Connected component with the bounding box of its node shapes.
+struct Component<'a> {
+    nodes: &'a [usize],
+    bounds: Rect,
+}
+
+impl<'a> Component<'a> {
+    fn new<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>, nodes: &'a [usize]) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let bounds = nodes
+            .iter()
+            .map(|&idx| {
+                let n = &g.g()[NodeIndex::new(idx)];
+                let half = Vec2::new(node_size(n, Vec2::X), node_size(n, Vec2::Y));
+                Rect::from_center_size(n.location(), half * 2.0)
+            })
+            .reduce(Rect::union)
+            .unwrap_or(Rect::NOTHING);
+        Self { nodes, bounds }
+    }
+}
+
+/// Packs `components` of `g` on shelves whose width is chosen so the packed rectangle
+/// approaches `aspect_ratio`. Does nothing for a single component.
+fn pack_components<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    components: &[Vec<usize>],
+    spacing: f32,
+    aspect_ratio: f32,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut movable: Vec<Component> = components
+        .iter()
+        .filter(|nodes| !nodes.iter().any(|&idx| g.g()[NodeIndex::new(idx)].pinned()))
+        .map(|nodes| Component::new(g, nodes))
+        .collect();
+    if movable.len() < 2 {
+        return;
+    }
+    let anchor = movable
+        .iter()
+        .map(|c| c.bounds)
+        .reduce(Rect::union)
+        .unwrap()
+        .min;
+
+    // Tallest first; the sort is stable so equal heights keep insertion order.
+    movable.sort_by(|a, b| b.bounds.height().total_cmp(&a.bounds.height()));
+    let area: f32 = movable
+        .iter()
+        .map(|c| (c.bounds.width() + spacing) * (c.bounds.height() + spacing))
+        .sum();
+    let widest = movable.iter().map(|c| c.bounds.width()).fold(0.0, f32::max);
+    let aspect_ratio = if aspect_ratio.is_finite() && aspect_ratio > 0.0 {
+        aspect_ratio
+    } else {
+        1.0
+    };
+    let shelf_width = (area * aspect_ratio).sqrt().max(widest);
+
+    let (mut x, mut y, mut shelf_height) = (0.0f32, 0.0f32, 0.0f32);
+    for c in movable {
+        let size = c.bounds.size();
+        if x > 0.0 && x + size.x > shelf_width {
+            y += shelf_height + spacing;
+            x = 0.0;
+            shelf_height = 0.0;
+        }
+        let delta = anchor + Vec2::new(x, y) - c.bounds.min;
+        if delta != Vec2::ZERO {
+            for &idx in c.nodes {
+                let node = g.node_mut(NodeIndex::new(idx)).unwrap();
+                node.set_location(node.location() + delta);
+            }
+        }
+        x += size.x + spacing;
+        shelf_height = shelf_height.max(size.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        layouts::circular::{Circle, CircleState},
+        DefaultEdgeShape, DefaultNodeShape,
+    };
+    use egui::Pos2;
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Undirected, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    #[test]
+    fn packs_components_without_changing_their_shape() {
+        let mut sg: StableGraph<(), (), petgraph::Undirected> = StableGraph::default();
+        let a = sg.add_node(());
+        let b = sg.add_node(());
+        let c = sg.add_node(());
+        let d = sg.add_node(());
+        sg.add_edge(a, b, ());
+        sg.add_edge(c, d, ());
+        let mut g: G = Graph::from(&sg);
+        for (idx, pos) in [a, b, c, d].into_iter().zip([
+            Pos2::new(0.0, 0.0),
+            Pos2::new(40.0, 0.0),
+            Pos2::new(1000.0, 700.0),
+            Pos2::new(1000.0, 740.0),
+        ]) {
+            g.node_mut(idx).unwrap().set_location(pos);
+        }
+
+        let comps = components(&g);
+        pack_components(&mut g, &comps, 10.0, 10.0);
+
+        let loc = |idx| g.node(idx).unwrap().location();
+        // Default radius is 5: the taller vertical pair goes first at the anchor (-5, -5),
+        // the horizontal pair follows on the same shelf after a 10 unit gap.
+        assert_eq!(loc(c), Pos2::new(0.0, 0.0));
+        assert_eq!(loc(d), Pos2::new(0.0, 40.0));
+        assert_eq!(loc(a), Pos2::new(20.0, 0.0));
+        assert_eq!(loc(b), Pos2::new(60.0, 0.0));
+    }
+
+    #[test]
+    fn lays_out_components_separately() {
+        let mut sg: StableGraph<(), (), petgraph::Undirected> = StableGraph::default();
+        let idxs: Vec<_> = (0..6).map(|_| sg.add_node(())).collect();
+        for (a, b) in [(0, 1), (1, 2), (3, 4), (4, 5)] {
+            sg.add_edge(idxs[a], idxs[b], ());
+        }
+        let mut g: G = Graph::from(&sg);
+
+        let mut layout =
+            Packing::<CircleState, Circle>::from_state(State::<CircleState>::default());
+        let ctx = egui::Context::default();
+        let _ = ctx.run_ui(egui::RawInput::default(), |ui| layout.next(&mut g, ui));
+
+        let state = layout.state();
+        let mut keys: Vec<_> = state.components.keys().copied().collect();
+        keys.sort_unstable();
+        assert_eq!(keys, vec![0, 3]);
+        assert!(state.is_done());
+
+        // Each component is its own circle of three, not half of a circle of six.
+        for comp in [&idxs[..3], &idxs[3..]] {
+            let locs: Vec<Pos2> = comp
+                .iter()
+                .map(|&idx| g.node(idx).unwrap().location())
+                .collect();
+            let center = Pos2::new(
+                locs.iter().map(|p| p.x).sum::<f32>() / 3.0,
+                locs.iter().map(|p| p.y).sum::<f32>() / 3.0,
+            );
+            let r: Vec<f32> = locs.iter().map(|p| (*p - center).length()).collect();
+            assert!((r[0] - r[1]).abs() < 1e-3 && (r[1] - r[2]).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn keeps_components_until_structure_changes() {
+        let mut sg: StableGraph<(), (), petgraph::Undirected> = StableGraph::default();
+        let idxs: Vec<_> = (0..4).map(|_| sg.add_node(())).collect();
+        sg.add_edge(idxs[0], idxs[1], ());
+        sg.add_edge(idxs[2], idxs[3], ());
+        let mut g: G = Graph::from(&sg);
+
+        let mut state = State::<CircleState>::default();
+        let ctx = egui::Context::default();
+        let frame = |g: &mut G, state: State<CircleState>| {
+            let mut layout = Packing::<CircleState, Circle>::from_state(state);
+            let _ = ctx.run_ui(egui::RawInput::default(), |ui| layout.next(g, ui));
+            layout.state()
+        };
+        let built = |state: &State<CircleState>| {
+            let partition = state.partition.as_ref().unwrap().lock().unwrap();
+            (partition.structure, partition.subgraphs.len())
+        };
+
+        state = frame(&mut g, state);
+        let first = built(&state);
+        assert_eq!(first, (Some(g.structure()), 2));
+
+        // Moving nodes keeps the sub-graphs and hands them the new locations.
+        g.node_mut(idxs[0])
+            .unwrap()
+            .set_location(Pos2::new(500.0, 500.0));
+        state = frame(&mut g, state);
+        assert_eq!(built(&state), first);
+
+        g.add_edge(idxs[1], idxs[2], ());
+        state = frame(&mut g, state);
+        assert_eq!(built(&state), (Some(g.structure()), 0));
+        assert_eq!(
+            state.components.keys().copied().collect::<Vec<_>>(),
+            vec![0]
+        );
+    }
+}
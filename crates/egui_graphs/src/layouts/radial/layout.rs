@@ -0,0 +1,221 @@
+use std::f32::consts::TAU;
+
+use egui::{Pos2, Vec2};
+use petgraph::{csr::IndexType, stable_graph::NodeIndex, Direction::Incoming, EdgeType};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{hierarchical::Forest, Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Distance between consecutive depth rings.
+    pub ring_dist: f32,
+    /// Angle where the first subtree starts, in radians clockwise from the positive X axis.
+    pub start_angle: f32,
+    /// Total angle covered by the tree, in radians. `TAU` uses the full circle.
+    pub sweep_angle: f32,
+    /// Index of the node placed at the center. When unset (or the node does not exist),
+    /// nodes without incoming edges are used first, then the remaining nodes by degree.
+    pub root: Option<usize>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            ring_dist: 80.0,
+            start_angle: 0.0,
+            sweep_angle: TAU,
+            root: None,
+        }
+    }
+}
+
+impl LayoutState for State {
+    fn is_done(&self) -> bool {
+        self.triggered
+    }
+}
+
+/// Radial tree layout: the root sits at the center and every depth level goes on a
+/// concentric ring. Each subtree receives an angular wedge proportional to its leaf count.
+///
+/// The spanning forest is built like in [`crate::LayoutHierarchical`]; every tree of a
+/// forest gets its own set of rings, placed side by side.
+#[derive(Debug, Default)]
+pub struct Radial {
+    state: State,
+}
+
+impl Layout<State> for Radial {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        let forest = Forest::build_from(g, starts(g, self.state.root));
+        layout_forest(g, &forest, &self.state);
+
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Radial { state }
+    }
+}
+
+/// Tree roots in order of preference: the explicit root, nodes without incoming edges,
+/// then all nodes by descending degree so cycles and undirected graphs grow from hubs.
+fn starts<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    root: Option<usize>,
+) -> Vec<NodeIndex<Ix>>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut by_degree: Vec<NodeIndex<Ix>> = g.g().node_indices().collect();
+    by_degree.sort_by_key(|&n| std::cmp::Reverse(g.g().neighbors_undirected(n).count()));
+
+    root.map(NodeIndex::new)
+        .filter(|&r| g.g().contains_node(r))
+        .into_iter()
+        .chain(g.g().externals(Incoming))
+        .chain(by_degree)
+        .collect()
+}
+
+fn layout_forest<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    forest: &Forest<Ix>,
+    state: &State,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let n = forest.len();
+
+    // Children have larger ids than their parents, so a reverse sweep is a post-order.
+    let mut leaves = vec![0usize; n];
+    let mut height = vec![0usize; n];
+    for v in (0..n).rev() {
+        let kids = &forest.children[v];
+        leaves[v] = kids.iter().map(|&c| leaves[c]).sum::<usize>().max(1);
+        height[v] = kids.iter().map(|&c| height[c] + 1).max().unwrap_or(0);
+    }
+
+    // Wedge (start, span) of every subtree and the center of the tree it belongs to.
+    let mut wedge = vec![(0.0f32, 0.0f32); n];
+    let mut center = vec![Pos2::ZERO; n];
+    let mut cursor: Option<f32> = None;
+    for v in 0..n {
+        match forest.parent[v] {
+            Some(p) => center[v] = center[p],
+            None => {
+                let radius = height[v] as f32 * state.ring_dist;
+                let x = cursor.map_or(0.0, |c| c + state.ring_dist + radius);
+                cursor = Some(x + radius);
+                center[v] = Pos2::new(x, 0.0);
+                wedge[v] = (state.start_angle, state.sweep_angle);
+            }
+        }
+
+        let (mut start, span) = wedge[v];
+        for &c in &forest.children[v] {
+            let share = span * leaves[c] as f32 / leaves[v] as f32;
+            wedge[c] = (start, share);
+            start += share;
+        }
+    }
+
+    for v in 0..n {
+        let (start, span) = wedge[v];
+        let radius = forest.depth[v] as f32 * state.ring_dist;
+        let node = &mut g.g_mut()[forest.nodes[v]];
+        if !node.pinned() {
+            node.set_location(center[v] + radius * Vec2::angled(start + span / 2.0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    fn run(g: &mut G, state: &State) {
+        let forest = Forest::build_from(g, starts(g, state.root));
+        layout_forest(g, &forest, state);
+    }
+
+    #[test]
+    fn depth_levels_sit_on_rings() {
+        // 0 -> {1, 2}, 1 -> {3, 4, 5}
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let ns: Vec<_> = (0..6).map(|_| sg.add_node(())).collect();
+        for (a, b) in [(0, 1), (0, 2), (1, 3), (1, 4), (1, 5)] {
+            sg.add_edge(ns[a], ns[b], ());
+        }
+        let mut g: G = Graph::from(&sg);
+        run(&mut g, &State::default());
+
+        let loc = |i: usize| g.g()[ns[i]].location();
+        assert_eq!(loc(0), Pos2::ZERO);
+        for (i, depth) in [(1, 1.0), (2, 1.0), (3, 2.0), (4, 2.0), (5, 2.0)] {
+            assert!((loc(i).to_vec2().length() - depth * 80.0).abs() < 1e-3);
+        }
+
+        // Subtree of 1 has three of four leaves and so covers three quarters of the circle;
+        // its middle child lies in the same direction as 1 itself.
+        let dir = |i: usize| loc(i).to_vec2().normalized();
+        assert!((dir(1) - dir(4)).length() < 1e-3);
+        assert!((dir(1).angle() - dir(2).angle()).abs() > 1.0);
+    }
+
+    #[test]
+    fn explicit_root_and_cycles() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let ns: Vec<_> = (0..4).map(|_| sg.add_node(())).collect();
+        for i in 0..4 {
+            sg.add_edge(ns[i], ns[(i + 1) % 4], ());
+        }
+        let mut g: G = Graph::from(&sg);
+        let state = State {
+            root: Some(2),
+            ..State::default()
+        };
+        run(&mut g, &state);
+
+        assert_eq!(g.g()[ns[2]].location(), Pos2::ZERO);
+        let r = g.g()[ns[1]].location().to_vec2().length();
+        assert!((r - 3.0 * 80.0).abs() < 1e-3);
+    }
+}
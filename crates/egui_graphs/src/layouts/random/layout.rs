@@ -1,6 +1,6 @@
-use egui::Pos2;
+use egui::{Pos2, Rect, Vec2};
 use petgraph::stable_graph::IndexType;
-use rand::Rng;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -9,14 +9,46 @@ use crate::{
 };
 const SPAWN_SIZE: f32 = 250.;
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct State {
-    triggered: bool,
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Seed for reproducible placement. Each node's position depends only on the seed and
+    /// its index. `None` draws from the thread-local RNG.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Canvas rectangle nodes are spawned in.
+    #[serde(default = "default_spawn_rect")]
+    pub spawn_rect: Rect,
+    /// Only place nodes whose location was never set and keep checking for such nodes
+    /// every frame, so nodes added later are placed without moving the existing ones.
+    #[serde(default)]
+    pub only_unset: bool,
 }
 
-impl LayoutState for State {}
+fn default_spawn_rect() -> Rect {
+    Rect::from_min_size(Pos2::ZERO, Vec2::splat(SPAWN_SIZE))
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            seed: None,
+            spawn_rect: default_spawn_rect(),
+            only_unset: false,
+        }
+    }
+}
 
-/// Randomly places nodes on the canvas. Does not override existing locations. Applies once.
+impl LayoutState for State {
+    fn is_done(&self) -> bool {
+        self.triggered && !self.only_unset
+    }
+}
+
+/// Randomly places nodes inside [`State::spawn_rect`]. Applies once, or keeps placing
+/// newly added nodes when [`State::only_unset`] is enabled.
 #[derive(Debug, Default)]
 pub struct Random {
     state: State,
@@ -32,17 +64,11 @@ impl Layout<State> for Random {
         Dn: crate::DisplayNode<N, E, Ty, Ix>,
         De: crate::DisplayEdge<N, E, Ty, Ix, Dn>,
     {
-        if self.state.triggered {
+        if self.state.triggered && !self.state.only_unset {
             return;
         }
 
-        let mut rng = rand::rng();
-        for node in g.g_mut().node_weights_mut() {
-            node.set_location(Pos2::new(
-                rng.random_range(0. ..SPAWN_SIZE),
-                rng.random_range(0. ..SPAWN_SIZE),
-            ));
-        }
+        self.place(g);
 
         self.state.triggered = true;
     }
@@ -55,3 +81,112 @@ impl Layout<State> for Random {
         Self { state }
     }
 }
+
+impl Random {
+    fn place<N, E, Ty, Ix, Dn, De>(&self, g: &mut Graph<N, E, Ty, Ix, Dn, De>)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: petgraph::EdgeType,
+        Ix: IndexType,
+        Dn: crate::DisplayNode<N, E, Ty, Ix>,
+        De: crate::DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let rect = self.state.spawn_rect;
+        let mut thread_rng = rand::rng();
+        let indices: Vec<_> = g.g().node_indices().collect();
+        for idx in indices {
+            let node = &mut g.g_mut()[idx];
+            if node.pinned() || (self.state.only_unset && node.location_set()) {
+                continue;
+            }
+            let pos = match self.state.seed {
+                Some(seed) => {
+                    // Mixing in the index keeps positions independent of iteration order.
+                    let mut rng = StdRng::seed_from_u64(
+                        seed ^ (idx.index() as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15),
+                    );
+                    random_pos(&mut rng, rect)
+                }
+                None => random_pos(&mut thread_rng, rect),
+            };
+            node.set_location(pos);
+        }
+    }
+}
+
+fn random_pos(rng: &mut impl Rng, rect: Rect) -> Pos2 {
+    let mut sample = |lo: f32, hi: f32| {
+        if hi > lo {
+            rng.random_range(lo..hi)
+        } else {
+            lo
+        }
+    };
+    Pos2::new(
+        sample(rect.min.x, rect.max.x),
+        sample(rect.min.y, rect.max.y),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    fn graph(n: usize) -> G {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        for _ in 0..n {
+            sg.add_node(());
+        }
+        Graph::from(&sg)
+    }
+
+    fn locations(g: &G) -> Vec<Pos2> {
+        g.nodes_iter().map(|(_, n)| n.location()).collect()
+    }
+
+    #[test]
+    fn seed_is_reproducible_and_bounded() {
+        let state = State {
+            seed: Some(7),
+            spawn_rect: Rect::from_min_max(Pos2::new(-10.0, 20.0), Pos2::new(10.0, 30.0)),
+            ..State::default()
+        };
+        let (mut a, mut b) = (graph(20), graph(20));
+        Random {
+            state: state.clone(),
+        }
+        .place(&mut a);
+        Random {
+            state: state.clone(),
+        }
+        .place(&mut b);
+
+        assert_eq!(locations(&a), locations(&b));
+        assert!(locations(&a).iter().all(|p| state.spawn_rect.contains(*p)));
+    }
+
+    #[test]
+    fn only_unset_keeps_placed_nodes() {
+        let mut g = graph(3);
+        let state = State {
+            only_unset: true,
+            ..State::default()
+        };
+        Random {
+            state: state.clone(),
+        }
+        .place(&mut g);
+        let before = locations(&g);
+
+        let added = g.add_node(());
+        Random { state }.place(&mut g);
+
+        assert_eq!(locations(&g)[..3], before[..]);
+        assert!(g.node(added).unwrap().location_set());
+    }
+}
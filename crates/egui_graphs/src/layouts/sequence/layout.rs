@@ -0,0 +1,187 @@
+use std::marker::PhantomData;
+
+use petgraph::{stable_graph::IndexType, EdgeType};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    layouts::{AnimatedState, Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+/// State of [`Sequence`]: the states of both stages and which one is active.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "S1: Serialize, S2: Serialize",
+    deserialize = "S1: DeserializeOwned, S2: DeserializeOwned"
+))]
+pub struct State<S1, S2> {
+    /// State of the first stage.
+    pub first: S1,
+    /// State of the second stage. Nest another [`State`] here for longer pipelines.
+    pub second: S2,
+    /// Set once the first stage finished, the second stage runs from then on.
+    pub first_done: bool,
+    /// An animated first stage is finished once its average displacement per step drops
+    /// below this value.
+    pub min_avg_displacement: f32,
+}
+
+impl<S1: Default, S2: Default> Default for State<S1, S2> {
+    fn default() -> Self {
+        Self {
+            first: S1::default(),
+            second: S2::default(),
+            first_done: false,
+            min_avg_displacement: 0.1,
+        }
+    }
+}
+
+impl<S1, S2> LayoutState for State<S1, S2>
+where
+    S1: LayoutState + Clone,
+    S2: LayoutState + Clone,
+{
+    fn is_done(&self) -> bool {
+        self.first_done && self.second.is_done()
+    }
+
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        if self.first_done {
+            self.second.as_animated()
+        } else {
+            self.first.as_animated()
+        }
+    }
+}
+
+impl<S1: LayoutState, S2> State<S1, S2> {
+    /// Whether the first stage reported [`LayoutState::is_done`] or, if animated, settled
+    /// below [`State::min_avg_displacement`].
+    fn first_finished(&self) -> bool {
+        self.first.is_done()
+            || self
+                .first
+                .as_animated()
+                .and_then(AnimatedState::last_avg_displacement)
+                .is_some_and(|d| d < self.min_avg_displacement)
+    }
+}
+
+/// Runs layout `L1` until it is done, then hands the graph over to `L2`.
+///
+/// Each stage keeps its own state inside [`State`], so the whole pipeline is saved and
+/// loaded like any other layout state. Longer pipelines nest sequences as the second stage,
+/// e.g. random placement, then stress majorization, then Fruchterman-Reingold:
+///
+/// ```
+/// use egui_graphs::{
+///     FruchtermanReingold, FruchtermanReingoldState, LayoutForceDirected, LayoutRandom,
+///     LayoutSequence, LayoutStateRandom, LayoutStateSequence, LayoutStateStress, LayoutStress,
+/// };
+///
+/// type Refine = LayoutSequence<
+///     LayoutStateStress,
+///     LayoutStress,
+///     FruchtermanReingoldState,
+///     LayoutForceDirected<FruchtermanReingold>,
+/// >;
+/// type L = LayoutSequence<
+///     LayoutStateRandom,
+///     LayoutRandom,
+///     LayoutStateSequence<LayoutStateStress, FruchtermanReingoldState>,
+///     Refine,
+/// >;
+/// type S = LayoutStateSequence<
+///     LayoutStateRandom,
+///     LayoutStateSequence<LayoutStateStress, FruchtermanReingoldState>,
+/// >;
+///
+/// fn show(ui: &mut egui::Ui, graph: &mut egui_graphs::Graph) {
+///     ui.add(&mut egui_graphs::GraphView::<_, _, _, _, _, _, S, L>::new(graph));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Sequence<S1, L1, S2, L2> {
+    state: State<S1, S2>,
+    _layouts: PhantomData<fn() -> (L1, L2)>,
+}
+
+impl<S1: Default, L1, S2: Default, L2> Default for Sequence<S1, L1, S2, L2> {
+    fn default() -> Self {
+        Self {
+            state: State::default(),
+            _layouts: PhantomData,
+        }
+    }
+}
+
+impl<S1, L1, S2, L2> Layout<State<S1, S2>> for Sequence<S1, L1, S2, L2>
+where
+    S1: LayoutState + Clone,
+    L1: Layout<S1>,
+    S2: LayoutState + Clone,
+    L2: Layout<S2>,
+{
+    fn from_state(state: State<S1, S2>) -> impl Layout<State<S1, S2>> {
+        Self {
+            state,
+            _layouts: PhantomData,
+        }
+    }
+
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, ui: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.first_done {
+            let mut second = L2::from_state(self.state.second.clone());
+            second.next(g, ui);
+            self.state.second = second.state();
+            return;
+        }
+
+        let mut first = L1::from_state(self.state.first.clone());
+        first.next(g, ui);
+        self.state.first = first.state();
+        self.state.first_done = self.state.first_finished();
+    }
+
+    fn state(&self) -> State<S1, S2> {
+        self.state.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layouts::{hierarchical, random};
+    use crate::FruchtermanReingoldState;
+
+    #[test]
+    fn first_stage_finishes_when_done_or_settled() {
+        let mut state = State::<random::State, hierarchical::State>::default();
+        assert!(!state.first_finished());
+        state.first.triggered = true;
+        assert!(state.first_finished());
+
+        let mut state = State::<FruchtermanReingoldState, random::State>::default();
+        assert!(!state.first_finished());
+        state.first.last_avg_displacement = Some(1.0);
+        assert!(!state.first_finished());
+        state.first.last_avg_displacement = Some(0.05);
+        assert!(state.first_finished());
+
+        state.first_done = true;
+        let json = serde_json::to_string(&state).unwrap();
+        let back: State<FruchtermanReingoldState, random::State> =
+            serde_json::from_str(&json).unwrap();
+        assert!(back.first_done);
+        assert!(!back.is_done());
+    }
+}
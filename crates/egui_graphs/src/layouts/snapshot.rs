@@ -0,0 +1,82 @@
+//! Payload-free copies of a graph that layouts step on instead of the source graph.
+
+use egui::{Pos2, Rect, Vec2};
+use petgraph::{csr::IndexType, Directed, EdgeType, Undirected};
+
+use crate::{draw::DrawContext, DefaultEdgeShape, DisplayNode, Graph, NodeProps};
+
+/// Graph holding only the structure, locations and sizes of another graph.
+pub(crate) type SnapshotGraph<Ty> = Graph<(), (), Ty, usize, SnapshotNodeShape, DefaultEdgeShape>;
+
+/// [`SnapshotGraph`] of either direction, picked at runtime from the source graph.
+#[derive(Debug)]
+pub(crate) enum Snapshot {
+    Directed(SnapshotGraph<Directed>),
+    Undirected(SnapshotGraph<Undirected>),
+}
+
+/// Node display of the snapshot graph: a box with the half extents of the source node
+/// along both axes, which is all the size-aware forces read through [`crate::node_size`].
+#[derive(Debug, Clone)]
+pub(crate) struct SnapshotNodeShape {
+    pos: Pos2,
+    pub(crate) half: Vec2,
+}
+
+impl<N: Clone> From<NodeProps<N>> for SnapshotNodeShape {
+    fn from(props: NodeProps<N>) -> Self {
+        Self {
+            pos: props.location(),
+            half: Vec2::ZERO,
+        }
+    }
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
+    for SnapshotNodeShape
+{
+    fn closest_boundary_point(&self, dir: Vec2) -> Pos2 {
+        let t = [(dir.x, self.half.x), (dir.y, self.half.y)]
+            .into_iter()
+            .filter(|(d, _)| *d != 0.0)
+            .map(|(d, h)| h / d.abs())
+            .fold(f32::INFINITY, f32::min);
+        if t.is_finite() {
+            self.pos + dir * t
+        } else {
+            self.pos
+        }
+    }
+
+    fn shapes(&mut self, _: &DrawContext) -> Vec<egui::Shape> {
+        Vec::new()
+    }
+
+    fn update(&mut self, state: &NodeProps<N>) {
+        self.pos = state.location();
+    }
+
+    fn is_inside(&self, pos: Pos2) -> bool {
+        Rect::from_center_size(self.pos, self.half * 2.0).contains(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_keeps_extents_along_both_axes() {
+        let shape = SnapshotNodeShape {
+            pos: Pos2::new(10.0, 10.0),
+            half: Vec2::new(30.0, 5.0),
+        };
+        let size = |dir: Vec2| {
+            let a = DisplayNode::<(), (), Directed, usize>::closest_boundary_point(&shape, dir);
+            let b = DisplayNode::<(), (), Directed, usize>::closest_boundary_point(&shape, -dir);
+            (b - a).length() / 2.0
+        };
+        assert!((size(Vec2::X) - 30.0).abs() < 1e-4);
+        assert!((size(Vec2::Y) - 5.0).abs() < 1e-4);
+    }
+}
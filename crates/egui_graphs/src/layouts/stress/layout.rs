@@ -0,0 +1,468 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use egui::{Pos2, Vec2};
+use petgraph::{
+    csr::IndexType,
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+    EdgeType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{AnimatedState, Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    pub is_running: bool,
+    /// Desired canvas distance for one unit of graph-theoretic distance (one unweighted hop).
+    pub edge_length: f32,
+    /// Number of majorization sweeps performed per frame.
+    pub iterations_per_frame: u32,
+    /// Sweeps stop once the average node displacement falls below this value.
+    /// They resume automatically when the graph structure or weights change or a node is dragged.
+    pub tolerance: f32,
+    /// Optional per-edge lengths keyed by edge index. Missing edges count as `1.0`.
+    /// Fill via [`State::with_edge_weights`].
+    pub edge_weights: HashMap<usize, f32>,
+    #[serde(skip)]
+    pub last_avg_displacement: Option<f32>,
+    /// Total number of sweeps executed.
+    pub step_count: u64,
+
+    #[serde(skip)]
+    cache: Option<Arc<Distances>>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            is_running: true,
+            edge_length: 80.0,
+            iterations_per_frame: 1,
+            tolerance: 0.05,
+            edge_weights: HashMap::new(),
+            last_avg_displacement: None,
+            step_count: 0,
+            cache: None,
+        }
+    }
+}
+
+impl LayoutState for State {
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        Some(self)
+    }
+}
+
+impl AnimatedState for State {
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+    fn set_running(&mut self, v: bool) {
+        self.is_running = v;
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        self.last_avg_displacement
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.last_avg_displacement = v;
+    }
+    fn step_count(&self) -> u64 {
+        self.step_count
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.step_count = v;
+    }
+}
+
+impl State {
+    /// Uses `f` over every edge payload of `g` as that edge's length in the shortest-path
+    /// computation. Lengths must be positive; non-positive or non-finite values are ignored.
+    ///
+    /// Weights are keyed by edge index, call again after changing the graph edges.
+    pub fn with_edge_weights<N, E, Ty, Ix, Dn, De>(
+        mut self,
+        g: &Graph<N, E, Ty, Ix, Dn, De>,
+        f: impl Fn(&E) -> f32,
+    ) -> Self
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.edge_weights = g
+            .edges_iter()
+            .filter_map(|(idx, e)| {
+                let w = f(e.payload());
+                (w.is_finite() && w > 0.0).then_some((idx.index(), w))
+            })
+            .collect();
+        self.cache = None;
+        self
+    }
+}
+
+/// All-pairs graph distances for the current graph structure.
+#[derive(Debug)]
+struct Distances {
+    signature: u64,
+    /// Row-major `n * n` matrix of shortest-path distances in graph units.
+    d: Vec<f32>,
+    n: usize,
+}
+
+/// Stress majorization layout.
+///
+/// Places nodes so that canvas distances match graph-theoretic shortest-path distances,
+/// minimizing the stress `sum w_ij (|x_i - x_j| - d_ij)^2` with `w_ij = d_ij^-2`.
+/// Runs incrementally: every frame performs `iterations_per_frame` localized majorization
+/// sweeps and reports the average displacement through [`AnimatedState`].
+///
+/// Disconnected node pairs are kept apart at slightly more than the graph diameter.
+#[derive(Debug, Default)]
+pub struct Stress {
+    state: State,
+}
+
+impl Layout<State> for Stress {
+    fn from_state(state: State) -> impl Layout<State> {
+        Self { state }
+    }
+
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        self.step(g);
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+}
+
+impl Stress {
+    fn step<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if !self.state.is_running || g.node_count() < 2 {
+            return;
+        }
+
+        let indices: Vec<_> = g.g().node_indices().collect();
+        let signature = signature(g, &self.state.edge_weights);
+        let stale = self
+            .state
+            .cache
+            .as_ref()
+            .is_none_or(|c| c.signature != signature);
+        if stale {
+            self.state.cache = Some(Arc::new(Distances {
+                signature,
+                d: all_pairs_distances(g, &indices, &self.state.edge_weights),
+                n: indices.len(),
+            }));
+            self.state.last_avg_displacement = None;
+        }
+
+        // Idle once converged, but keep reacting while the user drags a node around.
+        let converged = self
+            .state
+            .last_avg_displacement
+            .is_some_and(|avg| avg < self.state.tolerance);
+        if converged && g.dragged_node().is_none() {
+            return;
+        }
+
+        let dist = Arc::clone(self.state.cache.as_ref().unwrap());
+        let mut pos: Vec<Pos2> = indices
+            .iter()
+            .map(|&idx| g.node(idx).unwrap().location())
+            .collect();
+        let pinned: Vec<bool> = indices
+            .iter()
+            .map(|&idx| g.node(idx).unwrap().pinned())
+            .collect();
+        if self.state.step_count == 0 {
+            spread_if_degenerate(&mut pos, &pinned, self.state.edge_length);
+        }
+
+        let mut avg = 0.0;
+        for _ in 0..self.state.iterations_per_frame.max(1) {
+            avg = sweep(&mut pos, &pinned, &dist, self.state.edge_length);
+            self.state.step_count += 1;
+        }
+
+        for (i, &idx) in indices.iter().enumerate() {
+            if !pinned[i] && pos[i].x.is_finite() && pos[i].y.is_finite() {
+                g.node_mut(idx).unwrap().set_location(pos[i]);
+            }
+        }
+        self.state.last_avg_displacement = Some(avg);
+    }
+}
+
+/// One localized majorization (Gauss-Seidel) sweep over all nodes. Pinned nodes stay
+/// put and act as anchors for the others. Returns average node displacement.
+fn sweep(pos: &mut [Pos2], pinned: &[bool], dist: &Distances, scale: f32) -> f32 {
+    let n = dist.n;
+    let mut total = 0.0;
+    for i in 0..n {
+        if pinned[i] {
+            continue;
+        }
+        let (mut num, mut den) = (Vec2::ZERO, 0.0f32);
+        let xi = pos[i];
+        for (j, &dij) in dist.d[i * n..(i + 1) * n].iter().enumerate() {
+            let d = dij * scale;
+            if i == j || d <= 0.0 {
+                continue;
+            }
+            let w = 1.0 / (d * d);
+            let xj = pos[j];
+            let mut delta = xi - xj;
+            let mut len = delta.length();
+            if len < 1e-6 {
+                // Deterministic nudge for coincident nodes.
+                delta = Vec2::angled(i as f32 * 2.399 + j as f32);
+                len = 1.0;
+            }
+            num += w * (xj.to_vec2() + delta * (d / len));
+            den += w;
+        }
+        if den > 0.0 {
+            let new = (num / den).to_pos2();
+            total += (new - xi).length();
+            pos[i] = new;
+        }
+    }
+    total / n as f32
+}
+
+/// Spreads nodes over a golden-angle spiral when they all share (almost) the same location,
+/// e.g. when a graph was just created and has not been placed by any layout yet.
+fn spread_if_degenerate(pos: &mut [Pos2], pinned: &[bool], scale: f32) {
+    let mut bounds = egui::Rect::NOTHING;
+    for p in pos.iter() {
+        bounds.extend_with(*p);
+    }
+    if bounds.width() > 1e-3 || bounds.height() > 1e-3 {
+        return;
+    }
+    let center = bounds.center();
+    for (i, p) in pos.iter_mut().enumerate() {
+        if pinned[i] {
+            continue;
+        }
+        let r = scale * (i as f32).sqrt();
+        *p = center + Vec2::angled(i as f32 * 2.399_963) * r;
+    }
+}
+
+fn signature<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    weights: &HashMap<usize, f32>,
+) -> u64
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let mut h = DefaultHasher::new();
+    for idx in g.g().node_indices() {
+        idx.index().hash(&mut h);
+    }
+    for e in g.g().edge_references() {
+        (e.source().index(), e.target().index()).hash(&mut h);
+        weights
+            .get(&e.id().index())
+            .copied()
+            .unwrap_or(1.0)
+            .to_bits()
+            .hash(&mut h);
+    }
+    h.finish()
+}
+
+#[derive(PartialEq)]
+struct Entry(f32, usize);
+
+impl Eq for Entry {}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    // Reversed for a min-heap on distance.
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+/// Undirected all-pairs shortest paths (Dijkstra from every node).
+/// Unreachable pairs get `diameter + 1`.
+fn all_pairs_distances<N, E, Ty, Ix, Dn, De>(
+    g: &Graph<N, E, Ty, Ix, Dn, De>,
+    indices: &[NodeIndex<Ix>],
+    weights: &HashMap<usize, f32>,
+) -> Vec<f32>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let n = indices.len();
+    let pos_of: HashMap<NodeIndex<Ix>, usize> = indices
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| (idx, i))
+        .collect();
+    let mut adj: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+    for e in g.g().edge_references() {
+        let (a, b) = (pos_of[&e.source()], pos_of[&e.target()]);
+        if a == b {
+            continue;
+        }
+        let w = weights.get(&e.id().index()).copied().unwrap_or(1.0);
+        adj[a].push((b, w));
+        adj[b].push((a, w));
+    }
+
+    let mut d = vec![f32::INFINITY; n * n];
+    let mut heap = BinaryHeap::new();
+    for s in 0..n {
+        let row = &mut d[s * n..(s + 1) * n];
+        row[s] = 0.0;
+        heap.push(Entry(0.0, s));
+        while let Some(Entry(du, u)) = heap.pop() {
+            if du > row[u] {
+                continue;
+            }
+            for &(v, w) in &adj[u] {
+                let nd = du + w;
+                if nd < row[v] {
+                    row[v] = nd;
+                    heap.push(Entry(nd, v));
+                }
+            }
+        }
+    }
+
+    let diameter = d
+        .iter()
+        .copied()
+        .filter(|v| v.is_finite())
+        .fold(0.0f32, f32::max);
+    for v in &mut d {
+        if !v.is_finite() {
+            *v = diameter + 1.0;
+        }
+    }
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{to_graph, DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<
+        (),
+        f32,
+        petgraph::Directed,
+        petgraph::stable_graph::DefaultIx,
+        DefaultNodeShape,
+        DefaultEdgeShape,
+    >;
+
+    fn path(weights: &[f32]) -> G {
+        let mut sg: StableGraph<(), f32> = StableGraph::default();
+        let mut prev = sg.add_node(());
+        for &w in weights {
+            let n = sg.add_node(());
+            sg.add_edge(prev, n, w);
+            prev = n;
+        }
+        to_graph(&sg)
+    }
+
+    fn run(g: &mut G, state: State, sweeps: usize) -> State {
+        let mut layout = Stress { state };
+        for _ in 0..sweeps {
+            layout.step(g);
+        }
+        layout.state
+    }
+
+    fn dist(g: &G, a: usize, b: usize) -> f32 {
+        let a = g.node(NodeIndex::new(a)).unwrap().location();
+        let b = g.node(NodeIndex::new(b)).unwrap().location();
+        (a - b).length()
+    }
+
+    #[test]
+    fn path_distances_match_graph_distances() {
+        let mut g = path(&[1.0, 1.0, 1.0]);
+        let state = run(&mut g, State::default(), 200);
+        let l = state.edge_length;
+        assert!((dist(&g, 0, 1) - l).abs() < 0.05 * l);
+        assert!((dist(&g, 0, 3) - 3.0 * l).abs() < 0.05 * l);
+        assert!(state.last_avg_displacement.unwrap() < state.tolerance);
+    }
+
+    #[test]
+    fn edge_weights_scale_target_distances() {
+        let mut g = path(&[1.0, 3.0]);
+        let state = State::default().with_edge_weights(&g, |w| *w);
+        run(&mut g, state, 200);
+        let ratio = dist(&g, 1, 2) / dist(&g, 0, 1);
+        assert!((ratio - 3.0).abs() < 0.15, "ratio {ratio}");
+    }
+
+    #[test]
+    fn restarts_when_graph_changes() {
+        let mut g = path(&[1.0]);
+        let state = run(&mut g, State::default(), 100);
+        let steps = state.step_count;
+        let converged = run(&mut g, state, 1);
+        assert_eq!(converged.step_count, steps, "converged layout should idle");
+
+        let a = NodeIndex::new(0);
+        let c = g.add_node(());
+        g.add_edge(a, c, 1.0);
+        let restarted = run(&mut g, converged, 1);
+        assert!(restarted.step_count > steps);
+    }
+}
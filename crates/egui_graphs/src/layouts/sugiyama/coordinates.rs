@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::layering::Layered;
+
+/// Brandes-Köpf horizontal coordinate assignment.
+///
+/// Computes four candidate placements (aligning each node with its upper or lower
+/// median neighbour, compacting towards the left or the right), aligns them to the
+/// narrowest one and takes the average of the two middle values per node. Long edges
+/// become straight vertical runs of dummy nodes wherever possible.
+///
+/// `width[v]` is the extent of node `v` along its layer; neighbours within a layer are
+/// kept `node_sep` apart, dummy nodes only `edge_sep`.
+pub(super) fn assign(lg: &Layered, width: &[f32], node_sep: f32, edge_sep: f32) -> Vec<f32> {
+    let n = lg.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let conflicts = type1_conflicts(lg);
+    let gap = |v: usize| if lg.is_dummy(v) { edge_sep } else { node_sep };
+    let sep = |u: usize, v: usize| (width[u] + width[v]) / 2.0 + (gap(u) + gap(v)) / 2.0;
+
+    let mut candidates: Vec<Vec<f32>> = Vec::with_capacity(4);
+    for upward in [true, false] {
+        let vertical: Vec<Vec<usize>> = if upward {
+            lg.layers.clone()
+        } else {
+            lg.layers.iter().rev().cloned().collect()
+        };
+        let neighbours = if upward { &lg.preds } else { &lg.succs };
+        for rightward in [false, true] {
+            let layering: Vec<Vec<usize>> = if rightward {
+                vertical
+                    .iter()
+                    .map(|l| l.iter().rev().copied().collect())
+                    .collect()
+            } else {
+                vertical.clone()
+            };
+            let root = vertical_alignment(n, &layering, &conflicts, neighbours);
+            let mut xs = horizontal_compaction(n, &layering, &root, &sep);
+            if rightward {
+                xs.iter_mut().for_each(|x| *x = -*x);
+            }
+            candidates.push(xs);
+        }
+    }
+
+    align_to_narrowest(&mut candidates, width);
+
+    (0..n)
+        .map(|v| {
+            let mut xs = [
+                candidates[0][v],
+                candidates[1][v],
+                candidates[2][v],
+                candidates[3][v],
+            ];
+            xs.sort_by(f32::total_cmp);
+            (xs[1] + xs[2]) / 2.0
+        })
+        .collect()
+}
+
+fn conflict_key(u: usize, v: usize) -> (usize, usize) {
+    (u.min(v), u.max(v))
+}
+
+/// Marks non-inner segments crossing an inner segment (an edge between two dummies).
+/// Inner segments take priority so long edges stay straight.
+fn type1_conflicts(lg: &Layered) -> HashSet<(usize, usize)> {
+    let mut conflicts = HashSet::new();
+    let mut pos = vec![0usize; lg.len()];
+    for layer in &lg.layers {
+        for (i, &v) in layer.iter().enumerate() {
+            pos[v] = i;
+        }
+    }
+
+    for pair in lg.layers.windows(2) {
+        let (prev, layer) = (&pair[0], &pair[1]);
+        let mut k0 = 0;
+        let mut scan = 0;
+        for (i, &v) in layer.iter().enumerate() {
+            let inner = if lg.is_dummy(v) {
+                lg.preds[v].iter().copied().find(|&u| lg.is_dummy(u))
+            } else {
+                None
+            };
+            if inner.is_none() && i + 1 != layer.len() {
+                continue;
+            }
+            let k1 = inner.map_or(prev.len(), |w| pos[w]);
+            for &s in &layer[scan..=i] {
+                for &u in &lg.preds[s] {
+                    let p = pos[u];
+                    if (p < k0 || k1 < p) && !(lg.is_dummy(u) && lg.is_dummy(s)) {
+                        conflicts.insert(conflict_key(u, s));
+                    }
+                }
+            }
+            scan = i + 1;
+            k0 = k1;
+        }
+    }
+    conflicts
+}
+
+/// Groups nodes into blocks by aligning each with a median neighbour in the
+/// previous layer of `layering`, skipping conflicting and crossing alignments.
+/// Returns the block root of every node.
+fn vertical_alignment(
+    n: usize,
+    layering: &[Vec<usize>],
+    conflicts: &HashSet<(usize, usize)>,
+    neighbours: &[Vec<usize>],
+) -> Vec<usize> {
+    let mut root: Vec<usize> = (0..n).collect();
+    let mut align: Vec<usize> = (0..n).collect();
+    let mut pos = vec![0usize; n];
+    for layer in layering {
+        for (i, &v) in layer.iter().enumerate() {
+            pos[v] = i;
+        }
+    }
+
+    for layer in layering {
+        let mut prev: Option<usize> = None;
+        for &v in layer {
+            let mut ws = neighbours[v].clone();
+            if ws.is_empty() {
+                continue;
+            }
+            ws.sort_by_key(|&w| pos[w]);
+            let last = ws.len() - 1;
+            for &w in &ws[last / 2..=last.div_ceil(2)] {
+                if align[v] == v
+                    && prev.is_none_or(|p| p < pos[w])
+                    && !conflicts.contains(&conflict_key(v, w))
+                {
+                    align[w] = v;
+                    root[v] = root[w];
+                    align[v] = root[v];
+                    prev = Some(pos[w]);
+                }
+            }
+        }
+    }
+    root
+}
+
+/// Places blocks as far left as their separation constraints allow, then pulls
+/// each towards its right neighbour to remove slack.
+fn horizontal_compaction(
+    n: usize,
+    layering: &[Vec<usize>],
+    root: &[usize],
+    sep: &impl Fn(usize, usize) -> f32,
+) -> Vec<f32> {
+    // Block graph: an edge from the block left of `v` to the block of `v`.
+    let mut edges: HashMap<(usize, usize), f32> = HashMap::new();
+    for layer in layering {
+        for pair in layer.windows(2) {
+            let (u, v) = (pair[0], pair[1]);
+            let d = sep(u, v);
+            let e = edges.entry((root[u], root[v])).or_insert(d);
+            *e = e.max(d);
+        }
+    }
+    let mut outs: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+    let mut ins: Vec<Vec<(usize, f32)>> = vec![Vec::new(); n];
+    let mut in_deg = vec![0usize; n];
+    for (&(a, b), &d) in &edges {
+        outs[a].push((b, d));
+        ins[b].push((a, d));
+        in_deg[b] += 1;
+    }
+
+    let blocks: Vec<usize> = (0..n).filter(|&v| root[v] == v).collect();
+    let mut topo = Vec::with_capacity(blocks.len());
+    let mut queue: VecDeque<usize> = blocks.iter().copied().filter(|&b| in_deg[b] == 0).collect();
+    while let Some(b) = queue.pop_front() {
+        topo.push(b);
+        for &(c, _) in &outs[b] {
+            in_deg[c] -= 1;
+            if in_deg[c] == 0 {
+                queue.push_back(c);
+            }
+        }
+    }
+
+    let mut xs = vec![0.0f32; n];
+    for &b in &topo {
+        xs[b] = ins[b].iter().map(|&(a, d)| xs[a] + d).fold(0.0, f32::max);
+    }
+    for &b in topo.iter().rev() {
+        let limit = outs[b]
+            .iter()
+            .map(|&(c, d)| xs[c] - d)
+            .fold(f32::INFINITY, f32::min);
+        if limit.is_finite() {
+            xs[b] = xs[b].max(limit);
+        }
+    }
+
+    (0..n).map(|v| xs[root[v]]).collect()
+}
+
+/// Shifts every candidate so that left-compacted ones share the left border and
+/// right-compacted ones the right border of the narrowest candidate.
+fn align_to_narrowest(candidates: &mut [Vec<f32>], width: &[f32]) {
+    let extent = |xs: &[f32]| {
+        xs.iter()
+            .zip(width)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), (&x, &w)| {
+                (lo.min(x - w / 2.0), hi.max(x + w / 2.0))
+            })
+    };
+    let bounds = |xs: &[f32]| {
+        xs.iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &x| {
+                (lo.min(x), hi.max(x))
+            })
+    };
+
+    let narrowest = (0..candidates.len())
+        .min_by(|&a, &b| {
+            let (la, ha) = extent(&candidates[a]);
+            let (lb, hb) = extent(&candidates[b]);
+            (ha - la).total_cmp(&(hb - lb))
+        })
+        .unwrap_or(0);
+    let (target_min, target_max) = bounds(&candidates[narrowest]);
+
+    for (i, xs) in candidates.iter_mut().enumerate() {
+        if i == narrowest {
+            continue;
+        }
+        let (lo, hi) = bounds(xs);
+        // Candidates alternate left / right compaction.
+        let delta = if i % 2 == 0 {
+            target_min - lo
+        } else {
+            target_max - hi
+        };
+        xs.iter_mut().for_each(|x| *x += delta);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn respects_separation_within_layers() {
+        let lg = Layered::build(5, &[(0, 1), (0, 2), (0, 3), (1, 4), (3, 4)]);
+        let width = vec![20.0; lg.len()];
+        let xs = assign(&lg, &width, 10.0, 5.0);
+        for layer in &lg.layers {
+            let mut row: Vec<f32> = layer.iter().map(|&v| xs[v]).collect();
+            row.sort_by(f32::total_cmp);
+            for pair in row.windows(2) {
+                assert!(pair[1] - pair[0] >= 30.0 - 1e-3, "{row:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn chain_is_straight() {
+        let lg = Layered::build(3, &[(0, 1), (1, 2)]);
+        let xs = assign(&lg, &[10.0; 3], 10.0, 5.0);
+        assert!((xs[0] - xs[1]).abs() < 1e-3 && (xs[1] - xs[2]).abs() < 1e-3);
+    }
+}
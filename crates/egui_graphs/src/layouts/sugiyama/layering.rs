@@ -0,0 +1,276 @@
+use std::collections::{HashSet, VecDeque};
+
+/// Proper layered graph: every edge connects two adjacent layers.
+///
+/// Ids `0..real` are the original graph nodes, ids `real..` are dummy nodes
+/// inserted along edges that span more than one layer.
+#[derive(Debug, Clone)]
+pub(super) struct Layered {
+    pub real: usize,
+    pub layer: Vec<usize>,
+    pub preds: Vec<Vec<usize>>,
+    pub succs: Vec<Vec<usize>>,
+    /// Nodes of every layer in their current order.
+    pub layers: Vec<Vec<usize>>,
+}
+
+impl Layered {
+    /// Builds the layered graph for `n` nodes connected by directed `edges`.
+    ///
+    /// Cycles are broken by reversing a small feedback arc set, self-loops and duplicate
+    /// edges are ignored.
+    pub fn build(n: usize, edges: &[(usize, usize)]) -> Self {
+        let edges = make_acyclic(n, edges);
+        let layer = longest_path_layering(n, &edges);
+
+        let mut lg = Self {
+            real: n,
+            layer,
+            preds: vec![Vec::new(); n],
+            succs: vec![Vec::new(); n],
+            layers: Vec::new(),
+        };
+        for (u, v) in edges {
+            let mut prev = u;
+            for l in lg.layer[u] + 1..lg.layer[v] {
+                let d = lg.add_dummy(l);
+                lg.connect(prev, d);
+                prev = d;
+            }
+            lg.connect(prev, v);
+        }
+        lg.init_order();
+        lg
+    }
+
+    pub fn len(&self) -> usize {
+        self.layer.len()
+    }
+
+    pub fn is_dummy(&self, v: usize) -> bool {
+        v >= self.real
+    }
+
+    fn add_dummy(&mut self, layer: usize) -> usize {
+        self.layer.push(layer);
+        self.preds.push(Vec::new());
+        self.succs.push(Vec::new());
+        self.layer.len() - 1
+    }
+
+    fn connect(&mut self, u: usize, v: usize) {
+        self.succs[u].push(v);
+        self.preds[v].push(u);
+    }
+
+    /// Initial order: depth-first from nodes in layer order, appending each node to its
+    /// layer when first visited. Keeps connected nodes close which gives the crossing
+    /// minimization a reasonable start.
+    fn init_order(&mut self) {
+        let depth = self.layer.iter().copied().max().map_or(0, |m| m + 1);
+        self.layers = vec![Vec::new(); depth];
+        let mut by_layer: Vec<usize> = (0..self.len()).collect();
+        by_layer.sort_by_key(|&v| self.layer[v]);
+
+        let mut visited = vec![false; self.len()];
+        let mut stack = Vec::new();
+        for start in by_layer {
+            if visited[start] {
+                continue;
+            }
+            stack.push(start);
+            while let Some(v) = stack.pop() {
+                if visited[v] {
+                    continue;
+                }
+                visited[v] = true;
+                self.layers[self.layer[v]].push(v);
+                stack.extend(self.succs[v].iter().rev().filter(|&&w| !visited[w]));
+            }
+        }
+    }
+}
+
+/// Removes self-loops and duplicates and reverses edges against the Eades-Lin-Smyth
+/// ordering so that the result is acyclic.
+fn make_acyclic(n: usize, edges: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    let order = eades_order(n, edges);
+    let mut seen = HashSet::new();
+    edges
+        .iter()
+        .filter(|(u, v)| u != v)
+        .map(|&(u, v)| if order[u] < order[v] { (u, v) } else { (v, u) })
+        .filter(|e| seen.insert(*e))
+        .collect()
+}
+
+/// Greedy feedback-arc-set heuristic (Eades, Lin and Smyth, 1993).
+/// Returns the position of each node in a sequence where few edges point backwards.
+fn eades_order(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut outs = vec![Vec::new(); n];
+    let mut ins = vec![Vec::new(); n];
+    for &(u, v) in edges {
+        if u != v {
+            outs[u].push(v);
+            ins[v].push(u);
+        }
+    }
+    let mut out_deg: Vec<isize> = outs.iter().map(|o| o.len() as isize).collect();
+    let mut in_deg: Vec<isize> = ins.iter().map(|i| i.len() as isize).collect();
+    let mut alive = vec![true; n];
+    let mut remaining = n;
+
+    let (mut head, mut tail) = (Vec::with_capacity(n), Vec::new());
+    let mut sinks: VecDeque<usize> = (0..n).filter(|&v| out_deg[v] == 0).collect();
+    let mut sources: VecDeque<usize> = (0..n).filter(|&v| in_deg[v] == 0).collect();
+
+    let remove = |v: usize,
+                  alive: &mut Vec<bool>,
+                  out_deg: &mut Vec<isize>,
+                  in_deg: &mut Vec<isize>,
+                  sinks: &mut VecDeque<usize>,
+                  sources: &mut VecDeque<usize>| {
+        alive[v] = false;
+        for &u in &ins[v] {
+            out_deg[u] -= 1;
+            if alive[u] && out_deg[u] == 0 {
+                sinks.push_back(u);
+            }
+        }
+        for &w in &outs[v] {
+            in_deg[w] -= 1;
+            if alive[w] && in_deg[w] == 0 {
+                sources.push_back(w);
+            }
+        }
+    };
+
+    while remaining > 0 {
+        if let Some(v) = sinks.pop_front() {
+            if alive[v] {
+                remove(
+                    v,
+                    &mut alive,
+                    &mut out_deg,
+                    &mut in_deg,
+                    &mut sinks,
+                    &mut sources,
+                );
+                tail.push(v);
+                remaining -= 1;
+            }
+            continue;
+        }
+        if let Some(v) = sources.pop_front() {
+            if alive[v] {
+                remove(
+                    v,
+                    &mut alive,
+                    &mut out_deg,
+                    &mut in_deg,
+                    &mut sinks,
+                    &mut sources,
+                );
+                head.push(v);
+                remaining -= 1;
+            }
+            continue;
+        }
+        let v = (0..n)
+            .filter(|&v| alive[v])
+            .max_by_key(|&v| (out_deg[v] - in_deg[v], std::cmp::Reverse(v)))
+            .unwrap();
+        remove(
+            v,
+            &mut alive,
+            &mut out_deg,
+            &mut in_deg,
+            &mut sinks,
+            &mut sources,
+        );
+        head.push(v);
+        remaining -= 1;
+    }
+
+    head.extend(tail.into_iter().rev());
+    let mut pos = vec![0; n];
+    for (i, v) in head.into_iter().enumerate() {
+        pos[v] = i;
+    }
+    pos
+}
+
+/// Longest-path layering of an acyclic graph. Sources that only feed deeper layers
+/// are pulled down next to their successors to avoid needlessly long edges.
+fn longest_path_layering(n: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut succs = vec![Vec::new(); n];
+    let mut in_deg = vec![0usize; n];
+    for &(u, v) in edges {
+        succs[u].push(v);
+        in_deg[v] += 1;
+    }
+
+    let mut topo = Vec::with_capacity(n);
+    let mut queue: VecDeque<usize> = (0..n).filter(|&v| in_deg[v] == 0).collect();
+    while let Some(v) = queue.pop_front() {
+        topo.push(v);
+        for &w in &succs[v] {
+            in_deg[w] -= 1;
+            if in_deg[w] == 0 {
+                queue.push_back(w);
+            }
+        }
+    }
+
+    let mut layer = vec![0usize; n];
+    let mut has_pred = vec![false; n];
+    for &v in &topo {
+        for &w in &succs[v] {
+            layer[w] = layer[w].max(layer[v] + 1);
+            has_pred[w] = true;
+        }
+    }
+    for &v in topo.iter().rev() {
+        if has_pred[v] {
+            continue;
+        }
+        if let Some(min_succ) = succs[v].iter().map(|&w| layer[w]).min() {
+            layer[v] = min_succ - 1;
+        }
+    }
+    layer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_cycles() {
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3)];
+        let lg = Layered::build(4, &edges);
+        for v in 0..lg.len() {
+            for &w in &lg.succs[v] {
+                assert_eq!(lg.layer[w], lg.layer[v] + 1);
+            }
+        }
+    }
+
+    #[test]
+    fn inserts_dummies_for_long_edges() {
+        // 0 -> 1 -> 2 and a long edge 0 -> 2.
+        let lg = Layered::build(3, &[(0, 1), (1, 2), (0, 2)]);
+        assert_eq!(lg.layer[..3], [0, 1, 2]);
+        assert_eq!(lg.len(), 4);
+        assert!(lg.is_dummy(3));
+        assert_eq!(lg.layer[3], 1);
+        assert_eq!(lg.layers.iter().map(Vec::len).sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn late_sources_are_pulled_down() {
+        // 0 -> 1 -> 2 and 3 -> 2: node 3 should sit on layer 1, not 0.
+        let lg = Layered::build(4, &[(0, 1), (1, 2), (3, 2)]);
+        assert_eq!(lg.layer[3], 1);
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use petgraph::{
+    csr::IndexType,
+    stable_graph::NodeIndex,
+    visit::{EdgeRef, IntoEdgeReferences},
+    EdgeType,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    layouts::{hierarchical::Orientation, Layout, LayoutState},
+    node_size, DisplayEdge, DisplayNode, Graph,
+};
+
+use super::{
+    coordinates,
+    layering::Layered,
+    ordering::{self, CrossingMinimization},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct State {
+    /// Run only once unless reset via GraphView::reset_layout or by setting `triggered = false`.
+    pub triggered: bool,
+    /// Gap between the borders of nodes on adjacent layers.
+    /// Interpreted as Y step for TopDown/BottomUp and X step for LeftRight/RightLeft.
+    pub row_dist: f32,
+    /// Gap between the borders of neighbouring nodes within a layer.
+    pub col_dist: f32,
+    /// Gap kept around the virtual bends of edges spanning several layers.
+    pub edge_dist: f32,
+    /// Layout orientation.
+    pub orientation: Orientation,
+    /// Heuristic used to reduce edge crossings.
+    pub crossing_minimization: CrossingMinimization,
+    /// Number of alternating down/up ordering sweeps.
+    pub crossing_sweeps: u32,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            triggered: false,
+            row_dist: 50.0,
+            col_dist: 30.0,
+            edge_dist: 10.0,
+            orientation: Orientation::TopDown,
+            crossing_minimization: CrossingMinimization::Barycenter,
+            crossing_sweeps: 24,
+        }
+    }
+}
+
+impl LayoutState for State {
+    fn is_done(&self) -> bool {
+        self.triggered
+    }
+}
+
+/// Layered (Sugiyama-style) layout for directed graphs.
+///
+/// Cycles are broken by reversing a greedy feedback arc set, nodes are assigned to layers
+/// by longest path, long edges are routed through virtual nodes, layers are reordered to
+/// reduce crossings and final coordinates are computed with Brandes-Köpf so that edges
+/// are as straight as possible. Node sizes are taken into account for spacing.
+#[derive(Debug, Default)]
+pub struct Sugiyama {
+    state: State,
+}
+
+impl Layout<State> for Sugiyama {
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, _: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.state.triggered {
+            return;
+        }
+
+        layout(g, &self.state);
+
+        self.state.triggered = true;
+    }
+
+    fn state(&self) -> State {
+        self.state.clone()
+    }
+
+    fn from_state(state: State) -> impl Layout<State> {
+        Sugiyama { state }
+    }
+}
+
+fn layout<N, E, Ty, Ix, Dn, De>(g: &mut Graph<N, E, Ty, Ix, Dn, De>, state: &State)
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    let indices: Vec<NodeIndex<Ix>> = g.g().node_indices().collect();
+    if indices.is_empty() {
+        return;
+    }
+    let pos_of: HashMap<NodeIndex<Ix>, usize> =
+        indices.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+    let edges: Vec<(usize, usize)> = g
+        .g()
+        .edge_references()
+        .map(|e| (pos_of[&e.source()], pos_of[&e.target()]))
+        .collect();
+
+    let mut lg = Layered::build(indices.len(), &edges);
+    ordering::minimize_crossings(&mut lg, state.crossing_minimization, state.crossing_sweeps);
+
+    // Node extents along and across layers; virtual nodes have none.
+    let along = state.orientation.level_axis();
+    let across = along.yx();
+    let mut width = vec![0.0; lg.len()];
+    let mut height = vec![0.0; lg.len()];
+    for (i, &idx) in indices.iter().enumerate() {
+        let node = &g.g()[idx];
+        width[i] = 2.0 * node_size(node, along);
+        height[i] = 2.0 * node_size(node, across);
+    }
+
+    let cols = coordinates::assign(&lg, &width, state.col_dist, state.edge_dist);
+
+    // Layers are spaced by their tallest node so that rows never overlap.
+    let mut rows = Vec::with_capacity(lg.layers.len());
+    let mut row = 0.0;
+    let mut prev_half = 0.0f32;
+    for (l, layer) in lg.layers.iter().enumerate() {
+        let half = layer.iter().map(|&v| height[v] / 2.0).fold(0.0, f32::max);
+        if l > 0 {
+            row += prev_half + state.row_dist + half;
+        }
+        rows.push(row);
+        prev_half = half;
+    }
+
+    let min_col = (0..indices.len())
+        .map(|i| cols[i])
+        .fold(f32::INFINITY, f32::min);
+    for (i, &idx) in indices.iter().enumerate() {
+        let node = &mut g.g_mut()[idx];
+        if !node.pinned() {
+            let loc = state
+                .orientation
+                .place(cols[i] - min_col, rows[lg.layer[i]]);
+            node.set_location(loc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    #[test]
+    fn edges_point_down_the_layers() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let a = sg.add_node(());
+        let b = sg.add_node(());
+        let c = sg.add_node(());
+        let d = sg.add_node(());
+        sg.add_edge(a, b, ());
+        sg.add_edge(a, c, ());
+        sg.add_edge(b, d, ());
+        sg.add_edge(c, d, ());
+        sg.add_edge(a, d, ());
+        let mut g: G = Graph::from(&sg);
+
+        layout(&mut g, &State::default());
+
+        let y = |n: NodeIndex| g.g()[n].location().y;
+        assert!(y(a) < y(b) && y(a) < y(c));
+        assert!(y(b) < y(d) && y(c) < y(d));
+        assert!((y(b) - y(c)).abs() < 1e-3);
+        assert!((g.g()[b].location().x - g.g()[c].location().x).abs() > 1.0);
+    }
+
+    #[test]
+    fn left_right_swaps_axes() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let a = sg.add_node(());
+        let b = sg.add_node(());
+        sg.add_edge(a, b, ());
+        let mut g: G = Graph::from(&sg);
+
+        let state = State {
+            orientation: Orientation::LeftRight,
+            ..State::default()
+        };
+        layout(&mut g, &state);
+
+        let (pa, pb) = (g.g()[a].location(), g.g()[b].location());
+        assert!(pa.x < pb.x);
+        assert!((pa.y - pb.y).abs() < 1e-3);
+    }
+
+    #[test]
+    fn cyclic_graph_is_laid_out() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        let ns: Vec<_> = (0..5).map(|_| sg.add_node(())).collect();
+        for i in 0..5 {
+            sg.add_edge(ns[i], ns[(i + 1) % 5], ());
+        }
+        let mut g: G = Graph::from(&sg);
+
+        layout(&mut g, &State::default());
+
+        let mut ys: Vec<f32> = ns.iter().map(|&n| g.g()[n].location().y).collect();
+        ys.sort_by(f32::total_cmp);
+        ys.dedup_by(|a, b| (*a - *b).abs() < 1e-3);
+        assert_eq!(ys.len(), 5);
+    }
+}
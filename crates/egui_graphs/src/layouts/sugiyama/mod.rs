@@ -0,0 +1,7 @@
+mod coordinates;
+mod layering;
+mod layout;
+mod ordering;
+
+pub use layout::{State, Sugiyama};
+pub use ordering::CrossingMinimization;
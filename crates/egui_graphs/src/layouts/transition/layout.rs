@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use egui::{emath::easing, Pos2};
+use petgraph::{
+    stable_graph::{IndexType, NodeIndex},
+    EdgeType,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    layouts::{AnimatedState, Layout, LayoutState},
+    DisplayEdge, DisplayNode, Graph,
+};
+
+/// Easing curve of a [`Transition`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum Easing {
+    Linear,
+    /// Decelerates towards the end.
+    CubicOut,
+    /// Accelerates at the start and decelerates towards the end.
+    #[default]
+    CubicInOut,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::CubicOut => easing::cubic_out(t),
+            Easing::CubicInOut => easing::cubic_in_out(t),
+        }
+    }
+}
+
+/// Node positions a running transition moves from and to, keyed by node index.
+#[derive(Debug, Clone, Default)]
+struct Tween {
+    from: HashMap<usize, Pos2>,
+    to: HashMap<usize, Pos2>,
+    elapsed: f32,
+}
+
+/// State of [`Transition`]: the wrapped layout state plus the animation settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "S: Serialize", deserialize = "S: DeserializeOwned"))]
+pub struct State<S> {
+    /// State of the wrapped layout.
+    pub inner: S,
+    /// Animation length in seconds. `0.0` applies new positions instantly.
+    pub duration: f32,
+    pub easing: Easing,
+    /// Smallest single-frame move of any node that starts an animation. Smaller moves, like
+    /// the steps of a running simulation, are applied directly.
+    pub min_jump: f32,
+    #[serde(skip)]
+    tween: Option<Tween>,
+}
+
+impl<S: Default> Default for State<S> {
+    fn default() -> Self {
+        Self {
+            inner: S::default(),
+            duration: 0.5,
+            easing: Easing::default(),
+            min_jump: 50.0,
+            tween: None,
+        }
+    }
+}
+
+impl<S: LayoutState + Clone> LayoutState for State<S> {
+    fn is_done(&self) -> bool {
+        self.tween.is_none() && self.inner.is_done()
+    }
+    fn as_animated(&self) -> Option<&dyn AnimatedState> {
+        self.inner.as_animated()
+    }
+}
+
+impl<S: AnimatedState> AnimatedState for State<S> {
+    fn is_running(&self) -> bool {
+        self.inner.is_running()
+    }
+    fn set_running(&mut self, v: bool) {
+        self.inner.set_running(v);
+    }
+    fn last_avg_displacement(&self) -> Option<f32> {
+        self.inner.last_avg_displacement()
+    }
+    fn set_last_avg_displacement(&mut self, v: Option<f32>) {
+        self.inner.set_last_avg_displacement(v);
+    }
+    fn step_count(&self) -> u64 {
+        self.inner.step_count()
+    }
+    fn set_step_count(&mut self, v: u64) {
+        self.inner.set_step_count(v);
+    }
+}
+
+impl<S> State<S> {
+    /// Whether nodes are currently being animated towards new positions.
+    pub fn is_animating(&self) -> bool {
+        self.tween.is_some()
+    }
+
+    /// Moves nodes back to the targets of the running animation so the wrapped layout
+    /// continues from its own positions. Returns the positions shown before that.
+    ///
+    /// A drag finishes the animation: every other node jumps to its target.
+    fn begin<N, E, Ty, Ix, Dn, De>(
+        &mut self,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    ) -> HashMap<usize, Pos2>
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let shown = locations(g);
+        if let Some(tween) = self.tween.take() {
+            set_locations(g, &tween.to);
+            if g.nodes_iter().any(|(_, n)| n.dragged()) {
+                return locations(g);
+            }
+            self.tween = Some(tween);
+        }
+        shown
+    }
+
+    /// Compares the positions produced by the wrapped layout with what was shown, starts or
+    /// retargets the animation and moves the nodes to their interpolated positions.
+    fn end<N, E, Ty, Ix, Dn, De>(
+        &mut self,
+        g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+        shown: &HashMap<usize, Pos2>,
+        dt: f32,
+    ) where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        if self.duration <= 0.0 {
+            self.tween = None;
+            return;
+        }
+        let targets = locations(g);
+        let previous = self.tween.as_ref().map_or(shown, |t| &t.to);
+        let jumped = targets.iter().any(|(idx, to)| {
+            previous
+                .get(idx)
+                .is_some_and(|from| from.distance(*to) >= self.min_jump)
+        });
+
+        if jumped {
+            self.tween = Some(Tween {
+                from: shown.clone(),
+                to: targets,
+                elapsed: 0.0,
+            });
+        } else if let Some(tween) = &mut self.tween {
+            tween.to = targets;
+        } else {
+            return;
+        }
+
+        let tween = self.tween.as_mut().unwrap();
+        tween.elapsed += dt;
+        let t = (tween.elapsed / self.duration).min(1.0);
+        if t >= 1.0 {
+            self.tween = None;
+            return;
+        }
+        let e = self.easing.apply(t);
+        for (&idx, &to) in &tween.to {
+            let Some(node) = g.node_mut(NodeIndex::new(idx)) else {
+                continue;
+            };
+            if node.pinned() || node.dragged() {
+                continue;
+            }
+            if let Some(&from) = tween.from.get(&idx) {
+                node.set_location(from.lerp(to, e));
+            }
+        }
+    }
+}
+
+fn locations<N, E, Ty, Ix, Dn, De>(g: &Graph<N, E, Ty, Ix, Dn, De>) -> HashMap<usize, Pos2>
+where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    g.nodes_iter()
+        .map(|(idx, n)| (idx.index(), n.location()))
+        .collect()
+}
+
+fn set_locations<N, E, Ty, Ix, Dn, De>(
+    g: &mut Graph<N, E, Ty, Ix, Dn, De>,
+    locations: &HashMap<usize, Pos2>,
+) where
+    N: Clone,
+    E: Clone,
+    Ty: EdgeType,
+    Ix: IndexType,
+    Dn: DisplayNode<N, E, Ty, Ix>,
+    De: DisplayEdge<N, E, Ty, Ix, Dn>,
+{
+    for (&idx, &loc) in locations {
+        if let Some(node) = g.node_mut(NodeIndex::new(idx)) {
+            if !node.pinned() && !node.dragged() {
+                node.set_location(loc);
+            }
+        }
+    }
+}
+
+/// Wraps a layout and animates nodes from their old to their new positions whenever the
+/// wrapped layout moves them by at least [`State::min_jump`], e.g. when a one-shot layout
+/// is applied or re-run.
+///
+/// The animation advances by the frame time, continues towards updated targets while the
+/// wrapped layout keeps running and finishes immediately when a node is dragged.
+#[derive(Debug)]
+pub struct Transition<S, L> {
+    state: State<S>,
+    _layout: PhantomData<fn() -> L>,
+}
+
+impl<S: Default, L> Default for Transition<S, L> {
+    fn default() -> Self {
+        Self {
+            state: State::default(),
+            _layout: PhantomData,
+        }
+    }
+}
+
+impl<S, L> Layout<State<S>> for Transition<S, L>
+where
+    S: LayoutState + Clone,
+    L: Layout<S>,
+{
+    fn from_state(state: State<S>) -> impl Layout<State<S>> {
+        Self {
+            state,
+            _layout: PhantomData,
+        }
+    }
+
+    fn next<N, E, Ty, Ix, Dn, De>(&mut self, g: &mut Graph<N, E, Ty, Ix, Dn, De>, ui: &egui::Ui)
+    where
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        Dn: DisplayNode<N, E, Ty, Ix>,
+        De: DisplayEdge<N, E, Ty, Ix, Dn>,
+    {
+        let shown = self.state.begin(g);
+
+        let mut inner = L::from_state(self.state.inner.clone());
+        inner.next(g, ui);
+        self.state.inner = inner.state();
+
+        let dt = ui.input(|i| i.stable_dt);
+        self.state.end(g, &shown, dt);
+    }
+
+    fn state(&self) -> State<S> {
+        self.state.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layouts::random, DefaultEdgeShape, DefaultNodeShape};
+    use petgraph::stable_graph::StableGraph;
+
+    type G = Graph<(), (), petgraph::Directed, u32, DefaultNodeShape, DefaultEdgeShape>;
+
+    #[test]
+    fn animates_jumps_and_finishes_on_drag() {
+        let mut sg: StableGraph<(), ()> = StableGraph::new();
+        sg.add_node(());
+        sg.add_node(());
+        let mut g: G = Graph::from(&sg);
+        let (a, b) = (NodeIndex::new(0), NodeIndex::new(1));
+        g.node_mut(a).unwrap().set_location(Pos2::ZERO);
+        g.node_mut(b).unwrap().set_location(Pos2::ZERO);
+        let mut state = State::<random::State> {
+            duration: 1.0,
+            easing: Easing::Linear,
+            ..Default::default()
+        };
+
+        // The wrapped layout moves `a` far away: it is shown a quarter of the way there.
+        let shown = state.begin(&mut g);
+        g.node_mut(a).unwrap().set_location(Pos2::new(100.0, 0.0));
+        state.end(&mut g, &shown, 0.25);
+        assert!(state.is_animating());
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(25.0, 0.0));
+        assert_eq!(g.node(b).unwrap().location(), Pos2::ZERO);
+
+        // Next frame the layout sees its own target again.
+        let shown = state.begin(&mut g);
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(100.0, 0.0));
+        state.end(&mut g, &shown, 0.25);
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(50.0, 0.0));
+
+        // Dragging any node finishes the animation at the targets.
+        g.node_mut(b).unwrap().set_dragged(true);
+        let shown = state.begin(&mut g);
+        state.end(&mut g, &shown, 0.25);
+        assert!(!state.is_animating());
+        assert_eq!(g.node(a).unwrap().location(), Pos2::new(100.0, 0.0));
+    }
+}
@@ -6,6 +6,7 @@ mod helpers;
 mod layouts;
 mod metadata;
 mod settings;
+mod spatial_index;
 
 pub use draw::{DefaultEdgeShape, DefaultNodeShape, DisplayEdge, DisplayNode, DrawContext};
 pub use elements::{Edge, EdgeProps, Node, NodeProps};
@@ -21,20 +22,42 @@ pub use helpers::{
     generate_simple_ungraph, node_size, to_graph, to_graph_custom,
 };
 
+pub use layouts::circular::{
+    Circle as LayoutCircle, CircleOrder as LayoutCircleOrder, CircleState as LayoutStateCircle,
+    Concentric as LayoutConcentric, ConcentricMetric as LayoutConcentricMetric,
+    ConcentricState as LayoutStateConcentric,
+};
 pub use layouts::force_directed::{
-    CenterGravity, CenterGravityParams, Extra, ForceAlgorithm,
-    ForceDirected as LayoutForceDirected, FruchtermanReingold, FruchtermanReingoldState,
-    FruchtermanReingoldWithCenterGravity, FruchtermanReingoldWithCenterGravityState,
-    FruchtermanReingoldWithExtras, FruchtermanReingoldWithExtrasState,
+    BoundingBox, BoundingBoxMode, BoundingBoxParams, CenterGravity, CenterGravityParams, Collision,
+    CollisionParams, DirectionalFlow, DirectionalFlowParams, Extra, ForceAlgorithm, ForceAtlas2,
+    ForceAtlas2State, ForceAtlas2WithCenterGravity, ForceAtlas2WithCenterGravityState,
+    ForceAtlas2WithExtras, ForceAtlas2WithExtrasState, ForceDirected as LayoutForceDirected,
+    ForceDirectedBackground as LayoutForceDirectedBackground,
+    ForceDirectedBackgroundState as LayoutStateForceDirectedBackground, FruchtermanReingold,
+    FruchtermanReingoldState, FruchtermanReingoldWithCenterGravity,
+    FruchtermanReingoldWithCenterGravityState, FruchtermanReingoldWithExtras,
+    FruchtermanReingoldWithExtrasState, GroupAttraction, GroupAttractionParams, ParallelEdges,
 };
 pub use layouts::hierarchical::{
     Hierarchical as LayoutHierarchical, Orientation as LayoutHierarchicalOrientation,
     State as LayoutStateHierarchical,
 };
+pub use layouts::packing::{Packing as LayoutPacking, State as LayoutStatePacking};
+pub use layouts::radial::{Radial as LayoutRadial, State as LayoutStateRadial};
 pub use layouts::random::{Random as LayoutRandom, State as LayoutStateRandom};
+pub use layouts::sequence::{Sequence as LayoutSequence, State as LayoutStateSequence};
+pub use layouts::stress::{State as LayoutStateStress, Stress as LayoutStress};
+pub use layouts::sugiyama::{
+    CrossingMinimization as LayoutSugiyamaCrossingMinimization, State as LayoutStateSugiyama,
+    Sugiyama as LayoutSugiyama,
+};
+pub use layouts::transition::{
+    Easing as LayoutTransitionEasing, State as LayoutStateTransition,
+    Transition as LayoutTransition,
+};
 pub use layouts::{Layout, LayoutState};
 pub use metadata::{reset_metadata, MetadataFrame};
-pub use settings::{SettingsInteraction, SettingsNavigation, SettingsStyle};
+pub use settings::{LevelOfDetail, SettingsInteraction, SettingsNavigation, SettingsStyle};
 
 #[cfg(feature = "events")]
 pub mod events;
@@ -175,6 +175,18 @@ pub fn reset_metadata(ui: &mut Ui, id: Option<String>) {
 pub struct MetadataInstance {
     pub last_top_left: Pos2,
     pub first_frame_pending: bool,
+    /// Ongoing box or lasso selection.
+    #[serde(default)]
+    pub selection_gesture: Option<SelectionGesture>,
+}
+
+/// Shape of an ongoing selection drag, in canvas coordinates.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SelectionGesture {
+    /// Start and current corner of the rectangle.
+    Box(Pos2, Pos2),
+    /// Points of the freeform path so far, closed back to the first one.
+    Lasso(Vec<Pos2>),
 }
 
 impl MetadataInstance {
@@ -190,6 +202,7 @@ impl MetadataInstance {
                 .unwrap_or(MetadataInstance {
                     last_top_left: fallback_top_left,
                     first_frame_pending: true,
+                    selection_gesture: None,
                 })
         })
     }
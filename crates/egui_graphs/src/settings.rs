@@ -4,11 +4,13 @@
 /// - If `dragging_enabled` is true, it implicitly enables node clicking and hover.
 /// - If `node_selection_enabled` or `edge_selection_enabled` is true, they implicitly enable node clicking and hover.
 /// - If `node_selection_multi_enabled` or `edge_selection_multi_enabled` is true, they implicitly enable selection, node clicking and hover.
+/// - If `box_selection_enabled` or `lasso_selection_enabled` is true, they implicitly enable node selection, node clicking and hover.
 ///
 /// Disabling a child while its master is enabled has no effect at runtime (the effective behavior still treats it as enabled).
 #[derive(Debug, Clone)]
 pub struct SettingsInteraction {
     pub(crate) dragging_enabled: bool,
+    pub(crate) node_pin_on_drag_enabled: bool,
     pub(crate) hover_enabled: bool,
     pub(crate) node_clicking_enabled: bool,
     pub(crate) node_selection_enabled: bool,
@@ -16,12 +18,15 @@ pub struct SettingsInteraction {
     pub(crate) edge_clicking_enabled: bool,
     pub(crate) edge_selection_enabled: bool,
     pub(crate) edge_selection_multi_enabled: bool,
+    pub(crate) box_selection_enabled: bool,
+    pub(crate) lasso_selection_enabled: bool,
 }
 
 impl Default for SettingsInteraction {
     fn default() -> Self {
         Self {
             dragging_enabled: true,
+            node_pin_on_drag_enabled: false,
             hover_enabled: true,
             node_clicking_enabled: false,
             node_selection_enabled: false,
@@ -29,6 +34,8 @@ impl Default for SettingsInteraction {
             edge_clicking_enabled: false,
             edge_selection_enabled: false,
             edge_selection_multi_enabled: false,
+            box_selection_enabled: false,
+            lasso_selection_enabled: false,
         }
     }
 }
@@ -47,6 +54,15 @@ impl SettingsInteraction {
         self
     }
 
+    /// Pins a node once the user finishes dragging it, so layouts leave it where it was dropped.
+    /// Has effect only while dragging is enabled.
+    ///
+    /// Default: `false`
+    pub fn with_node_pin_on_drag_enabled(mut self, enabled: bool) -> Self {
+        self.node_pin_on_drag_enabled = enabled;
+        self
+    }
+
     /// Enables hover detection for nodes and emits hover events when hovered node changes.
     /// Also sets pointing hand cursor when hovering a node or dragging.
     ///
@@ -115,6 +131,30 @@ impl SettingsInteraction {
         self.edge_selection_multi_enabled = enabled;
         self
     }
+
+    /// Selects nodes by dragging a rectangle with `Shift` held, enables node selection.
+    ///
+    /// Replaces the selection with the nodes whose shapes intersect the rectangle. Also
+    /// holding `Ctrl` (`Cmd` on macOS) adds them to the selection, holding `Alt` removes them
+    /// from it. Edges crossing the rectangle are included while edge selection is enabled.
+    ///
+    /// Default: `false`
+    pub fn with_box_selection_enabled(mut self, enabled: bool) -> Self {
+        self.box_selection_enabled = enabled;
+        self
+    }
+
+    /// Selects nodes by drawing a freeform path with `Shift` held, enables node selection.
+    ///
+    /// Works like box selection with the same modifiers, takes its place when both are
+    /// enabled. Nodes whose centers fall inside the closed path are selected, edges are left
+    /// as they are.
+    ///
+    /// Default: `false`
+    pub fn with_lasso_selection_enabled(mut self, enabled: bool) -> Self {
+        self.lasso_selection_enabled = enabled;
+        self
+    }
 }
 
 /// Represents graph navigation settings.
@@ -180,10 +220,27 @@ impl SettingsNavigation {
     }
 }
 
+/// Level of detail to draw with at the current zoom, see [`SettingsStyle::lod`].
+///
+/// Passed to custom displays in [`crate::DrawContext::lod`]; the default shapes follow it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LevelOfDetail {
+    /// Nodes as points, edges as straight lines without tips, no loops and no labels.
+    Minimal,
+    /// Full shapes, labels only for interacted nodes and selected edges.
+    NoLabels,
+    /// Everything.
+    #[default]
+    Full,
+}
+
 /// `SettingsStyle` stores settings for the style of the graph.
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct SettingsStyle {
     pub(crate) labels_always: bool,
+    pub(crate) labels_min_zoom: f32,
+    pub(crate) full_detail_min_zoom: f32,
+    pub(crate) viewport_culling: bool,
     // Optional user-provided hook to override node stroke (outline) styling.
     // Signature: `(selected, dragged, node_color, current_stroke, egui_style) -> new Stroke`.
     pub(crate) node_stroke_hook: Option<NodeStrokeHook>,
@@ -196,6 +253,9 @@ impl core::fmt::Debug for SettingsStyle {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("SettingsStyle")
             .field("labels_always", &self.labels_always)
+            .field("labels_min_zoom", &self.labels_min_zoom)
+            .field("full_detail_min_zoom", &self.full_detail_min_zoom)
+            .field("viewport_culling", &self.viewport_culling)
             .field(
                 "node_stroke_hook",
                 &self.node_stroke_hook.as_ref().map(|_| "<hook>"),
@@ -208,6 +268,19 @@ impl core::fmt::Debug for SettingsStyle {
     }
 }
 
+impl Default for SettingsStyle {
+    fn default() -> Self {
+        Self {
+            labels_always: false,
+            labels_min_zoom: 0.0,
+            full_detail_min_zoom: 0.0,
+            viewport_culling: false,
+            node_stroke_hook: None,
+            edge_stroke_hook: None,
+        }
+    }
+}
+
 impl SettingsStyle {
     /// Creates new [`SettingsStyle`] with default values.
     /// ```
@@ -226,6 +299,48 @@ impl SettingsStyle {
         self
     }
 
+    /// Below this zoom labels are only shown for interacted nodes and selected edges, even
+    /// with [`Self::with_labels_always`].
+    ///
+    /// Default is 0.0, labels are never hidden.
+    pub fn with_labels_min_zoom(mut self, zoom: f32) -> Self {
+        self.labels_min_zoom = zoom;
+        self
+    }
+
+    /// Below this zoom nodes are drawn as points and edges as plain lines, see
+    /// [`LevelOfDetail::Minimal`].
+    ///
+    /// Default is 0.0, shapes are never simplified.
+    pub fn with_full_detail_min_zoom(mut self, zoom: f32) -> Self {
+        self.full_detail_min_zoom = zoom;
+        self
+    }
+
+    /// Whether to skip nodes and edges whose bounds are outside of the widget.
+    ///
+    /// Nodes and edges are kept within a margin around the widget, shapes of custom displays
+    /// reaching further from their bounds than that may disappear early. Culling costs two
+    /// spatial index queries per frame and only pays off when most of a large graph is
+    /// off screen.
+    ///
+    /// Default is false.
+    pub fn with_viewport_culling(mut self, enabled: bool) -> Self {
+        self.viewport_culling = enabled;
+        self
+    }
+
+    /// Level of detail for the given zoom.
+    pub fn lod(&self, zoom: f32) -> LevelOfDetail {
+        if zoom < self.full_detail_min_zoom {
+            LevelOfDetail::Minimal
+        } else if zoom < self.labels_min_zoom {
+            LevelOfDetail::NoLabels
+        } else {
+            LevelOfDetail::Full
+        }
+    }
+
     /// Provide a hook to customize node stroke (outline) styling.
     /// The hook receives: `(selected, dragged, node_color, current_stroke, egui_style)` and should return a new `Stroke`.
     /// Example:
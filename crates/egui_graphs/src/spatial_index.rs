@@ -0,0 +1,178 @@
+use std::collections::{HashMap, HashSet};
+
+use egui::{Pos2, Rect};
+
+/// Side of a grid cell in canvas units.
+const CELL_SIZE: f32 = 64.0;
+/// Entries covering more cells than this are kept in a flat list instead of the grid.
+const MAX_CELLS: i64 = 64;
+
+type Cell = (i64, i64);
+
+/// Uniform grid over canvas coordinates for the nodes and edges of a [`crate::Graph`].
+///
+/// Stores the bounding rect of every entry, so queries return candidates whose bounds
+/// contain a point or intersect a rect. Exact hit-testing stays with the display shapes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpatialIndex {
+    pub nodes: Grid,
+    pub edges: Grid,
+    /// Nodes changed through the graph since the last refresh.
+    pub dirty_nodes: HashSet<usize>,
+    /// Edges changed through the graph since the last refresh.
+    pub dirty_edges: HashSet<usize>,
+    /// Graph generation of the last full sweep, `None` before the first one.
+    pub swept: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Grid {
+    bounds: HashMap<usize, Rect>,
+    cells: HashMap<Cell, Vec<usize>>,
+    oversized: Vec<usize>,
+}
+
+impl Grid {
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Moves `id` to `rect`. Does nothing when its bounds did not change.
+    pub fn set(&mut self, id: usize, rect: Rect) {
+        if self.bounds.get(&id) == Some(&rect) {
+            return;
+        }
+        self.remove(id);
+        if !rect.is_finite() {
+            return;
+        }
+        match cell_range(rect) {
+            Some((min, max)) => {
+                for cell in cells(min, max) {
+                    self.cells.entry(cell).or_default().push(id);
+                }
+            }
+            None => self.oversized.push(id),
+        }
+        self.bounds.insert(id, rect);
+    }
+
+    pub fn remove(&mut self, id: usize) {
+        let Some(rect) = self.bounds.remove(&id) else {
+            return;
+        };
+        match cell_range(rect) {
+            Some((min, max)) => {
+                for cell in cells(min, max) {
+                    if let Some(ids) = self.cells.get_mut(&cell) {
+                        ids.retain(|&i| i != id);
+                        if ids.is_empty() {
+                            self.cells.remove(&cell);
+                        }
+                    }
+                }
+            }
+            None => self.oversized.retain(|&i| i != id),
+        }
+    }
+
+    /// Drops every entry for which `keep` returns false.
+    pub fn retain(&mut self, keep: impl Fn(usize) -> bool) {
+        let stale: Vec<usize> = self
+            .bounds
+            .keys()
+            .copied()
+            .filter(|&id| !keep(id))
+            .collect();
+        for id in stale {
+            self.remove(id);
+        }
+    }
+
+    /// Entries whose bounds contain `pos`, sorted by id.
+    pub fn at(&self, pos: Pos2) -> Vec<usize> {
+        let mut res: Vec<usize> = self
+            .cells
+            .get(&cell_of(pos))
+            .into_iter()
+            .flatten()
+            .chain(&self.oversized)
+            .copied()
+            .filter(|id| self.bounds[id].contains(pos))
+            .collect();
+        res.sort_unstable();
+        res
+    }
+
+    /// Entries whose bounds intersect `rect`, sorted by id.
+    pub fn intersecting(&self, rect: Rect) -> Vec<usize> {
+        let (min, max) = (cell_of(rect.min), cell_of(rect.max));
+        let covered = (max.0 - min.0 + 1).saturating_mul(max.1 - min.1 + 1);
+        let mut res: Vec<usize> = if covered > self.cells.len() as i64 {
+            self.bounds
+                .iter()
+                .filter(|(_, b)| b.intersects(rect))
+                .map(|(&id, _)| id)
+                .collect()
+        } else {
+            cells(min, max)
+                .filter_map(|cell| self.cells.get(&cell))
+                .flatten()
+                .chain(&self.oversized)
+                .copied()
+                .filter(|id| self.bounds[id].intersects(rect))
+                .collect()
+        };
+        res.sort_unstable();
+        res.dedup();
+        res
+    }
+}
+
+fn cell_of(p: Pos2) -> Cell {
+    (
+        (p.x / CELL_SIZE).floor() as i64,
+        (p.y / CELL_SIZE).floor() as i64,
+    )
+}
+
+/// First and last cell covered by `rect`, `None` if it covers more than [`MAX_CELLS`].
+fn cell_range(rect: Rect) -> Option<(Cell, Cell)> {
+    let (min, max) = (cell_of(rect.min), cell_of(rect.max));
+    let covered = (max.0 - min.0 + 1).saturating_mul(max.1 - min.1 + 1);
+    (covered <= MAX_CELLS).then_some((min, max))
+}
+
+fn cells(min: Cell, max: Cell) -> impl Iterator<Item = Cell> {
+    (min.0..=max.0).flat_map(move |x| (min.1..=max.1).map(move |y| (x, y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::Vec2;
+
+    #[test]
+    fn moves_entries_and_returns_all_hits() {
+        let mut grid = Grid::default();
+        let rect =
+            |x: f32, y: f32, size: f32| Rect::from_min_size(Pos2::new(x, y), Vec2::splat(size));
+        grid.set(2, rect(0.0, 0.0, 10.0));
+        grid.set(0, rect(5.0, 5.0, 10.0));
+        grid.set(1, rect(-5000.0, -5000.0, 10_000.0));
+
+        assert_eq!(grid.at(Pos2::new(7.0, 7.0)), vec![0, 1, 2]);
+        assert_eq!(grid.at(Pos2::new(2.0, 2.0)), vec![1, 2]);
+        assert_eq!(grid.intersecting(rect(-1.0, -1.0, 2.0)), vec![1, 2]);
+
+        grid.set(2, rect(300.0, 300.0, 10.0));
+        assert_eq!(grid.at(Pos2::new(2.0, 2.0)), vec![1]);
+        assert_eq!(grid.at(Pos2::new(305.0, 305.0)), vec![1, 2]);
+
+        grid.retain(|id| id != 1);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.at(Pos2::new(305.0, 305.0)), vec![2]);
+        assert_eq!(grid.intersecting(rect(-1e6, -1e6, 2e6)), vec![0, 2]);
+    }
+}
@@ -19,7 +19,8 @@ fn test_serialize_deserialize_node() {
         value: 42,
         label: "A".to_string(),
     };
-    let node = Node::<TestNodePayload, TestEdgePayload>::new(payload.clone());
+    let mut node = Node::<TestNodePayload, TestEdgePayload>::new(payload.clone());
+    node.set_pinned(true);
     let json = serde_json::to_string(&node).expect("serialize node");
 
     let node2: Node<TestNodePayload, TestEdgePayload> =
@@ -27,11 +28,13 @@ fn test_serialize_deserialize_node() {
 
     assert_eq!(node2.color(), node.color());
     assert_eq!(node2.location(), node.location());
+    assert_eq!(node2.location_set(), node.location_set());
     assert_eq!(node2.payload(), node.payload());
     assert_eq!(node2.label(), node.label());
     assert_eq!(node2.selected(), node.selected());
     assert_eq!(node2.dragged(), node.dragged());
     assert_eq!(node2.hovered(), node.hovered());
+    assert!(node2.pinned());
 }
 
 #[test]